@@ -1,8 +1,30 @@
+pub mod animated;
+pub mod batch;
 pub mod cache;
+pub mod card;
+pub mod color;
+pub mod conditional;
+pub mod file;
+pub mod format;
+pub mod gif;
+pub mod info;
+pub mod jpeg;
 pub mod lock;
 pub mod logger;
+pub mod part;
 pub mod png;
+pub mod preview;
+pub mod query;
+pub mod raw;
+pub mod seven;
+pub mod sheet;
 pub mod skin;
+pub mod svg;
+pub mod team;
+pub mod url;
+pub mod validate;
+pub mod webp;
+pub mod zip;
 
 use std::sync::Arc;
 
@@ -17,7 +39,12 @@ use tokio::fs;
 const DOC_HTML_TEMPLATE: &str = include_str!("../.static/scalar.html");
 const DOC_HTML_PATH: &str = "./static/doc.html";
 
-use crate::{app::skin::skin_router, cache::Cache, rsync::lock::Lock};
+use crate::{
+    app::skin::{SKIN_PATH, skin_router},
+    cache::Cache,
+    remote::Remote,
+    rsync::lock::Lock,
+};
 #[inline]
 async fn health_check() -> status::NoContent {
     status::NoContent
@@ -29,20 +56,32 @@ async fn doc() -> Html {
 
 pub struct AppState {
     pub lock: Lock,
+    /// Teeworlds 0.7 skin parts
+    pub seven: Lock,
     pub cache: Cache,
+    /// Skins outside of the store
+    pub remote: Remote,
+    /// `max-age` of `Cache-Control` for rendered skins
+    pub max_age: u64,
 }
 
 pub async fn app(
     lock: Lock,
+    seven: Lock,
     cache: Cache,
+    remote: Remote,
+    max_age: u64,
     address: &str,
 ) {
     let router = Ohkami::new((
         Context::new(Arc::new(AppState {
             lock,
+            seven,
             cache,
+            remote,
+            max_age,
         })),
-        "/skin".By(skin_router()),
+        SKIN_PATH.By(skin_router()),
         "/health".GET(health_check),
     ));
 
@@ -0,0 +1,196 @@
+use std::{fmt::Display, sync::Arc};
+
+use ohkami::{
+    IntoResponse, Response,
+    claw::content::IntoContent,
+    fang::Context,
+    openapi::{self, Schema, SchemaRef, operation},
+    serde::Deserialize,
+};
+use serde::Serialize;
+use tracing::instrument;
+
+use crate::{
+    app::{
+        AppState,
+        color::Color,
+        conditional::Head,
+        format::Headed,
+        gif::Gif,
+        png::Png,
+        query::{FlatQuery, flatten},
+        skin::{Kind, RenderOptions, SkinQuery, TeeOptions, cached_render},
+    },
+    error::Error,
+};
+
+/// Default delay between frames in milliseconds
+pub const DEFAULT_DELAY: u16 = 100;
+/// Lower bound for delay between frames in milliseconds
+pub const MIN_DELAY: u16 = 20;
+/// Upper bound for delay between frames in milliseconds
+pub const MAX_DELAY: u16 = 1000;
+
+#[derive(Debug, Clone, Deserialize)]
+/// Animated skin query
+pub struct AnimatedQuery {
+    /// **note**: Replace all whitespaces to `_`
+    pub name: String,
+    /// Body color
+    pub body: Option<Color>,
+    /// Feet color
+    pub feet: Option<Color>,
+    /// Output format
+    #[serde(default)]
+    pub format: AnimationFormat,
+    /// Delay between frames in milliseconds
+    pub delay: Option<u16>,
+    /// How the tee is drawn, `eyes` between blinks
+    #[serde(flatten)]
+    pub tee: TeeOptions,
+    /// Output image options
+    #[serde(flatten)]
+    pub options: RenderOptions,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, Hash, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+/// Output format of animated skin
+pub enum AnimationFormat {
+    #[default]
+    Apng,
+    Gif,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Hash, PartialEq, Eq, Serialize)]
+/// Animation options of [SkinQuery]
+pub struct Animation {
+    pub format: AnimationFormat,
+    /// Delay between frames in milliseconds
+    pub delay: u16,
+}
+
+impl TryFrom<AnimatedQuery> for SkinQuery {
+    type Error = Error;
+
+    fn try_from(value: AnimatedQuery) -> Result<Self, Self::Error> {
+        let delay = value.delay.unwrap_or(DEFAULT_DELAY);
+        if !(MIN_DELAY..=MAX_DELAY).contains(&delay) {
+            return Err(Error::InvalidQuery(format!(
+                "delay must be in range {MIN_DELAY}..={MAX_DELAY}, got {delay}"
+            )));
+        }
+        let mut query = SkinQuery {
+            name: value.name,
+            body: value.body,
+            feet: value.feet,
+            tee: value.tee,
+            options: value.options,
+            kind: Kind::Animated(Animation {
+                format: value.format,
+                delay,
+            }),
+            ..Default::default()
+        };
+        query.validate()?;
+        Ok(query)
+    }
+}
+
+impl Schema for AnimatedQuery {
+    fn schema() -> impl Into<SchemaRef> {
+        openapi::component(
+            "AnimatedQuery",
+            flatten::<RenderOptions>(flatten::<TeeOptions>(
+                openapi::object()
+                    .property(
+                        "name",
+                        openapi::string()
+                            .format("a-zA-Z0-9_")
+                            .description("Skin name")
+                            .example("zzz"),
+                    )
+                    .optional("body", Color::schema())
+                    .optional("feet", Color::schema())
+                    .optional(
+                        "format",
+                        openapi::string()
+                            .description("Output format")
+                            .enumerates(["apng", "gif"])
+                            .default("apng"),
+                    )
+                    .optional(
+                        "delay",
+                        openapi::integer()
+                            .description("Delay between frames in milliseconds")
+                            .minimum(MIN_DELAY as i32)
+                            .maximum(MAX_DELAY as i32)
+                            .default(DEFAULT_DELAY),
+                    ),
+            )),
+        )
+    }
+}
+
+impl Display for AnimatedQuery {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        f.debug_struct("AnimatedQuery")
+            .field("name", &self.name)
+            .field("body", &self.body)
+            .field("feet", &self.feet)
+            .field("format", &self.format)
+            .field("delay", &self.delay)
+            .field("tee", &self.tee)
+            .field("options", &self.options)
+            .finish()
+    }
+}
+
+/// Rendered animation in one of [AnimationFormat]s
+#[derive(Debug)]
+pub struct AnimatedImage {
+    pub format: AnimationFormat,
+    /// `None` answers `HEAD` of an animation which isn't rendered
+    pub data: Option<Vec<u8>>,
+}
+
+impl IntoResponse for AnimatedImage {
+    #[inline]
+    fn into_response(self) -> Response {
+        match self.format {
+            AnimationFormat::Apng => Headed(self.data.map(Png)).into_response(),
+            AnimationFormat::Gif => Headed(self.data.map(Gif)).into_response(),
+        }
+    }
+
+    fn openapi_responses() -> openapi::Responses {
+        openapi::Responses::new([(
+            200,
+            openapi::Response::when("OK")
+                .content(Png::CONTENT_TYPE, Png::openapi_responsebody())
+                .content(Gif::CONTENT_TYPE, Gif::openapi_responsebody()),
+        )])
+    }
+}
+
+#[inline(always)]
+#[instrument(skip_all, level="info", fields(name=%query.name, body=?query.body, feet=?query.feet, format=?query.format, delay=?query.delay))]
+#[operation({
+    summary: "Get animated skin image",
+})]
+/// Represent GET method to return an animated skin by query
+pub async fn animated_handler(
+    Context(state): Context<'_, Arc<AppState>>,
+    FlatQuery(query): FlatQuery<AnimatedQuery>,
+    Head(head): Head,
+) -> Result<AnimatedImage, Error> {
+    let format = query.format;
+    let query = SkinQuery::try_from(query)?;
+    Ok(AnimatedImage {
+        format,
+        data: cached_render(state, query, head).await?,
+    })
+}
@@ -0,0 +1,227 @@
+use std::{
+    io::{Cursor, Write},
+    sync::Arc,
+};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use futures::{StreamExt, stream};
+use ohkami::{
+    IntoResponse, Json, Query, Response,
+    claw::{content::IntoContent, status::OK},
+    fang::Context,
+    openapi::{self, Schema, SchemaRef, operation},
+    serde::Deserialize,
+};
+use serde::Serialize;
+use serde_json::Value;
+use tracing::instrument;
+use zip::{CompressionMethod, ZipWriter, write::SimpleFileOptions};
+
+use crate::{
+    app::{AppState, format::Format, skin::SkinQuery, validate::MAX_CONCURRENT, zip::Zip},
+    error::{Error, ErrorResponse},
+};
+
+/// Upper bound for items of a batch
+pub const MAX_BATCH: usize = 128;
+
+/// Rendered image of an item in its [Format], with the fallback skin if it's rendered instead
+type Rendered = Result<(Format, Vec<u8>, Option<String>), Error>;
+
+#[derive(Debug, Deserialize)]
+/// JSON array of [SkinQuery], each item is parsed on its own to report errors per item
+pub struct BatchRequest(pub Vec<Value>);
+
+impl Schema for BatchRequest {
+    fn schema() -> impl Into<SchemaRef> {
+        openapi::array(SkinQuery::schema())
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+/// Batch output query
+pub struct BatchQuery {
+    #[serde(default)]
+    pub output: BatchOutput,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+/// How rendered batch is returned
+pub enum BatchOutput {
+    /// JSON list of [BatchItem]s
+    #[default]
+    Json,
+    /// ZIP archive of images with `errors.json` for failed items
+    Zip,
+}
+
+impl Schema for BatchQuery {
+    fn schema() -> impl Into<SchemaRef> {
+        openapi::component(
+            "BatchQuery",
+            openapi::object().optional(
+                "output",
+                openapi::string()
+                    .description("JSON list with base64 images or ZIP archive")
+                    .enumerates(["json", "zip"])
+                    .default("json"),
+            ),
+        )
+    }
+}
+
+#[derive(Debug, Serialize, Schema)]
+#[openapi(component)]
+/// Result of one [SkinQuery] of a batch, either an image or an error
+pub struct BatchItem {
+    /// Skin name, if the item has one
+    pub name: Option<String>,
+    /// MIME type of `data`
+    pub content_type: Option<String>,
+    /// Base64 encoded image
+    pub data: Option<String>,
+    /// Stored skin rendered instead of an unknown `name`
+    pub fallback: Option<String>,
+    pub error: Option<ErrorResponse>,
+}
+
+#[derive(Debug, Serialize)]
+/// Failed item of a ZIP archive
+struct ArchiveError {
+    index: usize,
+    name: Option<String>,
+    error: ErrorResponse,
+}
+
+/// Rendered batch in one of [BatchOutput]s
+#[derive(Debug)]
+pub enum BatchResponse {
+    Json(Vec<BatchItem>),
+    Zip(Vec<u8>),
+}
+
+impl IntoResponse for BatchResponse {
+    #[inline]
+    fn into_response(self) -> Response {
+        match self {
+            BatchResponse::Json(items) => Json(items).into_response(),
+            BatchResponse::Zip(data) => OK(Zip(data)).into_response(),
+        }
+    }
+
+    fn openapi_responses() -> openapi::Responses {
+        openapi::Responses::new([(
+            200,
+            openapi::Response::when("OK")
+                .content("application/json", openapi::array(BatchItem::schema()))
+                .content(Zip::CONTENT_TYPE, Zip::openapi_responsebody()),
+        )])
+    }
+}
+
+/// Parse and render one item like `GET /skin`, without `Accept` negotiation or validators
+async fn render_item(
+    state: &AppState,
+    value: Value,
+) -> Rendered {
+    let mut query: SkinQuery =
+        serde_json::from_value(value).map_err(|e| Error::InvalidQuery(e.to_string()))?;
+    query.validate()?;
+    let format = query.format.unwrap_or_default();
+    query.format = Some(format);
+    let (_, fallback) = state.lock.resolve(&mut query)?;
+    let data = match state.cache.get(&query).await {
+        Ok(Some(e)) => e.to_vec(),
+        _ => state.lock.get(state.cache.clone(), query).await?,
+    };
+    Ok((format, data, fallback))
+}
+
+/// Pack rendered items to ZIP, listing failed ones in `errors.json`
+fn archive(results: Vec<(Option<String>, Rendered)>) -> Result<Vec<u8>, Error> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    let mut errors = vec![];
+    for (index, (name, result)) in results.into_iter().enumerate() {
+        match result {
+            Ok((format, data, _)) => {
+                let file = name
+                    .as_deref()
+                    .unwrap_or_default()
+                    .replace(['/', '\\'], "_");
+                zip.start_file(format!("{index:03}_{file}.{}", format.extension()), options)?;
+                zip.write_all(&data)?;
+            }
+            Err(e) => errors.push(ArchiveError {
+                index,
+                name,
+                error: e.error_response(),
+            }),
+        }
+    }
+    if !errors.is_empty() {
+        zip.start_file("errors.json", options)?;
+        zip.write_all(&serde_json::to_vec_pretty(&errors)?)?;
+    }
+    Ok(zip.finish()?.into_inner())
+}
+
+#[inline(always)]
+#[instrument(skip_all, level="info", fields(len=%items.len(), output=?query.output))]
+#[operation({
+    summary: "Render many skins at once",
+})]
+/// Represent POST method to render a JSON array of skin queries in parallel
+pub async fn batch_handler(
+    Context(state): Context<'_, Arc<AppState>>,
+    Query(query): Query<BatchQuery>,
+    Json(BatchRequest(items)): Json<BatchRequest>,
+) -> Result<BatchResponse, Error> {
+    if items.len() > MAX_BATCH {
+        return Err(Error::InvalidQuery(format!(
+            "batch must have at most {MAX_BATCH} items, got {}",
+            items.len()
+        )));
+    }
+
+    let names = items
+        .iter()
+        .map(|item| item.get("name").and_then(Value::as_str).map(str::to_string))
+        .collect::<Vec<_>>();
+    let mut results = stream::iter(items.into_iter().enumerate())
+        .map(async |(index, item)| (index, render_item(state, item).await))
+        .buffer_unordered(MAX_CONCURRENT)
+        .collect::<Vec<_>>()
+        .await;
+    results.sort_by_key(|(index, _)| *index);
+    let results = names
+        .into_iter()
+        .zip(results.into_iter().map(|(_, result)| result))
+        .collect::<Vec<_>>();
+
+    match query.output {
+        BatchOutput::Json => Ok(BatchResponse::Json(
+            results
+                .into_iter()
+                .map(|(name, result)| match result {
+                    Ok((format, data, fallback)) => BatchItem {
+                        name,
+                        content_type: Some(format.mime().to_string()),
+                        data: Some(STANDARD.encode(data)),
+                        fallback,
+                        error: None,
+                    },
+                    Err(e) => BatchItem {
+                        name,
+                        content_type: None,
+                        data: None,
+                        fallback: None,
+                        error: Some(e.error_response()),
+                    },
+                })
+                .collect(),
+        )),
+        BatchOutput::Zip => Ok(BatchResponse::Zip(archive(results)?)),
+    }
+}
@@ -0,0 +1,232 @@
+use std::{fmt::Display, sync::Arc};
+
+use ohkami::{
+    fang::Context,
+    openapi::{self, Schema, SchemaRef, operation},
+    serde::Deserialize,
+};
+use tracing::instrument;
+
+use crate::{
+    app::{
+        AppState,
+        color::{Color, Rgb},
+        conditional::Head,
+        format::{Format, Headed},
+        query::{FlatQuery, flatten},
+        skin::{SkinQuery, TeeOptions},
+        svg::Svg,
+    },
+    error::Error,
+    render::card,
+};
+
+/// DDNet `MAX_NAME_LENGTH` without the terminating byte
+pub const MAX_PLAYER: usize = 15;
+/// DDNet `MAX_CLAN_LENGTH` without the terminating byte
+pub const MAX_CLAN: usize = 11;
+
+#[derive(Debug, Clone, Deserialize)]
+/// Player card query
+pub struct CardQuery {
+    /// Skin name
+    pub name: String,
+    /// Body color
+    pub body: Option<Color>,
+    /// Feet color
+    pub feet: Option<Color>,
+    /// How the tee is drawn, its size is fixed by the card
+    #[serde(flatten)]
+    pub tee: TeeOptions,
+    /// Player name, skin name if not set
+    pub player: Option<String>,
+    pub clan: Option<String>,
+    /// ISO 3166-1 alpha-2 code, shown as a flag
+    pub country: Option<String>,
+    #[serde(default)]
+    pub theme: Theme,
+    /// Overrides background color of the theme
+    pub background: Option<Rgb>,
+    /// Overrides text color of the theme
+    pub text: Option<Rgb>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+/// Color scheme of a card
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+}
+
+impl Theme {
+    pub const fn background(self) -> Rgb {
+        match self {
+            Theme::Dark => Rgb([30, 31, 34]),
+            Theme::Light => Rgb([255, 255, 255]),
+        }
+    }
+
+    pub const fn text(self) -> Rgb {
+        match self {
+            Theme::Dark => Rgb([242, 243, 245]),
+            Theme::Light => Rgb([30, 31, 34]),
+        }
+    }
+}
+
+impl CardQuery {
+    /// Check query values which can't be expressed by types
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.tee.size.is_some() {
+            return Err(Error::InvalidQuery(format!(
+                "size can't be set, the tee of a card is {}px",
+                card::TEE_SIZE
+            )));
+        }
+        if let Some(player) = &self.player
+            && player.chars().count() > MAX_PLAYER
+        {
+            return Err(Error::InvalidQuery(format!(
+                "player must have at most {MAX_PLAYER} characters"
+            )));
+        }
+        if let Some(clan) = &self.clan
+            && clan.chars().count() > MAX_CLAN
+        {
+            return Err(Error::InvalidQuery(format!(
+                "clan must have at most {MAX_CLAN} characters"
+            )));
+        }
+        if let Some(country) = &self.country
+            && !(country.len() == 2 && country.bytes().all(|b| b.is_ascii_alphabetic()))
+        {
+            return Err(Error::InvalidQuery(format!(
+                "country must be ISO 3166-1 alpha-2 code, got `{country}`"
+            )));
+        }
+        Ok(())
+    }
+
+    /// [SkinQuery] of the tee on the card
+    pub fn skin(&self) -> SkinQuery {
+        let mut query = SkinQuery {
+            name: self.name.clone(),
+            body: self.body,
+            feet: self.feet,
+            format: Some(Format::Png),
+            tee: TeeOptions {
+                size: Some(card::TEE_SIZE),
+                ..self.tee.clone()
+            },
+            ..Default::default()
+        };
+        query.normalize();
+        query
+    }
+}
+
+impl Schema for CardQuery {
+    fn schema() -> impl Into<SchemaRef> {
+        openapi::component(
+            "CardQuery",
+            // Own properties go last, so `size` replaces the flattened one
+            flatten::<TeeOptions>(openapi::object())
+                .property(
+                    "name",
+                    openapi::string()
+                        .format("a-zA-Z0-9_")
+                        .description("Skin name")
+                        .example("zzz"),
+                )
+                .optional("body", Color::schema())
+                .optional("feet", Color::schema())
+                .optional(
+                    "size",
+                    openapi::integer()
+                        .description("Not supported, the tee of a card has a fixed size")
+                        .nullable(),
+                )
+                .optional(
+                    "player",
+                    openapi::string()
+                        .description("Player name, skin name if not set")
+                        .example("nameless tee")
+                        .nullable(),
+                )
+                .optional("clan", openapi::string().example("DDNet").nullable())
+                .optional(
+                    "country",
+                    openapi::string()
+                        .description("ISO 3166-1 alpha-2 code, shown as a flag")
+                        .example("de")
+                        .nullable(),
+                )
+                .optional(
+                    "theme",
+                    openapi::string()
+                        .enumerates(["dark", "light"])
+                        .default("dark"),
+                )
+                .optional(
+                    "background",
+                    openapi::string()
+                        .description("Overrides background color of the theme")
+                        .example("#2b2d31")
+                        .nullable(),
+                )
+                .optional(
+                    "text",
+                    openapi::string()
+                        .description("Overrides text color of the theme")
+                        .example("white")
+                        .nullable(),
+                ),
+        )
+    }
+}
+
+impl Display for CardQuery {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        f.debug_struct("CardQuery")
+            .field("name", &self.name)
+            .field("body", &self.body)
+            .field("feet", &self.feet)
+            .field("tee", &self.tee)
+            .field("player", &self.player)
+            .field("clan", &self.clan)
+            .field("country", &self.country)
+            .field("theme", &self.theme)
+            .field("background", &self.background)
+            .field("text", &self.text)
+            .finish()
+    }
+}
+
+#[inline(always)]
+#[instrument(skip_all, level="info", fields(name=%query.name, player=?query.player, clan=?query.clan, theme=?query.theme))]
+#[operation({
+    summary: "Get SVG player card",
+})]
+/// Represent GET method to return the tee with player name, clan and country as SVG
+pub async fn card_handler(
+    Context(state): Context<'_, Arc<AppState>>,
+    FlatQuery(query): FlatQuery<CardQuery>,
+    Head(head): Head,
+) -> Result<Headed<Svg>, Error> {
+    query.validate()?;
+    let skin = query.skin();
+    if head {
+        state.lock.meta(&skin)?;
+        return Ok(Headed(None));
+    }
+    let tee = match state.cache.get(&skin).await {
+        Ok(Some(e)) => e.to_vec(),
+        _ => state.lock.get(state.cache.clone(), skin).await?,
+    };
+    Ok(Headed(Some(Svg(card::svg(&tee, &query)?))))
+}
@@ -0,0 +1,263 @@
+use std::{fmt::Display, str::FromStr};
+
+use ohkami::{
+    openapi::{self, Schema, SchemaRef},
+    serde::Deserialize,
+};
+use serde::Serialize;
+use tee_morphosis::tee::hsl::{HSL, ddnet_color_to_hsl};
+
+use crate::error::Error;
+
+/// Named colors of CSS Level 1 with `orange`
+const NAMED: [(&str, [u8; 3]); 17] = [
+    ("black", [0, 0, 0]),
+    ("silver", [192, 192, 192]),
+    ("gray", [128, 128, 128]),
+    ("white", [255, 255, 255]),
+    ("maroon", [128, 0, 0]),
+    ("red", [255, 0, 0]),
+    ("purple", [128, 0, 128]),
+    ("fuchsia", [255, 0, 255]),
+    ("green", [0, 128, 0]),
+    ("lime", [0, 255, 0]),
+    ("olive", [128, 128, 0]),
+    ("yellow", [255, 255, 0]),
+    ("navy", [0, 0, 128]),
+    ("blue", [0, 0, 255]),
+    ("teal", [0, 128, 128]),
+    ("aqua", [0, 255, 255]),
+    ("orange", [255, 165, 0]),
+];
+
+/// DDNet `DARKEST_LGT`, lightness below it can't be packed
+const DARKEST_LGT: f32 = 0.5;
+
+#[derive(Debug, Clone, Copy, Deserialize, Hash, PartialEq, Eq, Serialize)]
+#[serde(try_from = "ColorValue", into = "u32")]
+/// Tee color normalized to DDNet packed value.
+///
+/// Parsed from DDNet value, `#rrggbb`, `#rgb`, `rgb(r, g, b)`, `hsl(h, s%, l%)` or color name,
+/// so equal colors share a cache key.
+pub struct Color(pub u32);
+
+impl Color {
+    /// DDNet value without bits above its packed HSL, which DDNet ignores for body and feet
+    pub fn from_packed(value: u32) -> Self {
+        Self(value & 0xFF_FFFF)
+    }
+
+    /// Pack HSL with each component in `0.0..=1.0`.
+    ///
+    /// DDNet can't show colors darker than [DARKEST_LGT], so their lightness is clamped.
+    pub fn from_hsl((h, s, l): HSL) -> Self {
+        let byte = |value: f32| (value.clamp(0., 1.) * 255.).round() as u32;
+        let l = (l - DARKEST_LGT) / (1. - DARKEST_LGT);
+        Self((byte(h) % 256) << 16 | byte(s) << 8 | byte(l))
+    }
+
+    pub fn from_rgb([r, g, b]: [u8; 3]) -> Self {
+        let [r, g, b] = [r, g, b].map(|value| value as f32 / 255.);
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let l = (max + min) / 2.;
+        let d = max - min;
+        if d == 0. {
+            return Self::from_hsl((0., 0., l));
+        }
+        let s = d / (1. - (2. * l - 1.).abs());
+        let h = if max == r {
+            ((g - b) / d).rem_euclid(6.)
+        } else if max == g {
+            (b - r) / d + 2.
+        } else {
+            (r - g) / d + 4.
+        };
+        Self::from_hsl((h / 6., s, l))
+    }
+
+    pub fn hsl(&self) -> HSL {
+        ddnet_color_to_hsl(self.0)
+    }
+}
+
+impl FromStr for Color {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim().to_ascii_lowercase();
+        let invalid = || Error::InvalidQuery(format!("invalid color `{value}`"));
+
+        if let Ok(packed) = value.parse::<u32>() {
+            return Ok(Self::from_packed(packed));
+        }
+        if let Some(args) = function(&value, "hsl") {
+            return match args[..] {
+                [h, s, l] => {
+                    let number = |value: &str, max: f32| {
+                        value
+                            .trim_end_matches('%')
+                            .parse::<f32>()
+                            .ok()
+                            .filter(|value| (0. ..=max).contains(value))
+                            .ok_or_else(invalid)
+                    };
+                    Ok(Self::from_hsl((
+                        number(h, 360.)?.rem_euclid(360.) / 360.,
+                        number(s, 100.)? / 100.,
+                        number(l, 100.)? / 100.,
+                    )))
+                }
+                _ => Err(invalid()),
+            };
+        }
+        rgb(&value).map(Self::from_rgb).ok_or_else(invalid)
+    }
+}
+
+/// Parse `#rrggbb`, `#rgb`, `rgb(r, g, b)` or color name, keeping channels as is
+pub fn rgb(value: &str) -> Option<[u8; 3]> {
+    let value = value.trim().to_ascii_lowercase();
+    if let Some(hex) = value.strip_prefix('#') {
+        let channel = |at: usize, len: usize| {
+            let channel = u8::from_str_radix(hex.get(at..at + len)?, 16).ok()?;
+            Some(if len == 1 { channel * 17 } else { channel })
+        };
+        let len = match hex.len() {
+            3 => 1,
+            6 => 2,
+            _ => return None,
+        };
+        return Some([channel(0, len)?, channel(len, len)?, channel(2 * len, len)?]);
+    }
+    if let Some(args) = function(&value, "rgb") {
+        return match args[..] {
+            [r, g, b] => Some([r.parse().ok()?, g.parse().ok()?, b.parse().ok()?]),
+            _ => None,
+        };
+    }
+    NAMED
+        .iter()
+        .find(|(name, _)| *name == value)
+        .map(|(_, rgb)| *rgb)
+}
+
+/// Arguments of `name(a, b, c)`
+fn function<'a>(
+    value: &'a str,
+    name: &str,
+) -> Option<Vec<&'a str>> {
+    let args = value
+        .strip_prefix(name)?
+        .trim_start()
+        .strip_prefix('(')?
+        .strip_suffix(')')?;
+    Some(args.split(',').map(str::trim).collect())
+}
+
+#[derive(Deserialize)]
+#[serde(
+    untagged,
+    expecting = "color must be DDNet value, hex, `rgb(...)`, `hsl(...)` or color name"
+)]
+/// DDNet value as a JSON number, or any text [Color] is parsed from
+enum ColorValue {
+    Value(u32),
+    Text(String),
+}
+
+impl TryFrom<ColorValue> for Color {
+    type Error = Error;
+
+    fn try_from(value: ColorValue) -> Result<Self, Self::Error> {
+        match value {
+            ColorValue::Value(value) => Ok(Self::from_packed(value)),
+            ColorValue::Text(text) => text.parse(),
+        }
+    }
+}
+
+impl Schema for Color {
+    fn schema() -> impl Into<SchemaRef> {
+        openapi::component(
+            "Color",
+            openapi::string()
+                .description("DDNet value, hex, `rgb(...)`, `hsl(...)` or color name")
+                .example("#ff8800"),
+        )
+    }
+}
+
+impl From<Color> for u32 {
+    fn from(value: Color) -> Self {
+        value.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Hash, PartialEq, Eq, Serialize)]
+#[serde(try_from = "String", into = "String")]
+/// Color from hex, `rgb(...)` or color name, kept as is unlike [Color], written as `#rrggbb`
+pub struct Rgb(pub [u8; 3]);
+
+impl TryFrom<String> for Rgb {
+    type Error = Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        rgb(&value)
+            .map(Self)
+            .ok_or_else(|| Error::InvalidQuery(format!("invalid color `{value}`")))
+    }
+}
+
+impl From<Rgb> for String {
+    fn from(value: Rgb) -> Self {
+        value.to_string()
+    }
+}
+
+impl Display for Rgb {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        let [r, g, b] = self.0;
+        write!(f, "#{r:02x}{g:02x}{b:02x}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::app::color::Color;
+
+    #[test]
+    fn color_parsing() {
+        let red = Color(0x00_FF_00);
+        assert_eq!("65280".parse::<Color>().unwrap(), red);
+        assert_eq!("15355922".parse::<Color>().unwrap(), Color(15355922));
+        assert_eq!("32133138".parse::<Color>().unwrap(), Color(15355922));
+        assert_eq!("#ff0000".parse::<Color>().unwrap(), red);
+        assert_eq!("#F00".parse::<Color>().unwrap(), red);
+        assert_eq!("rgb(255, 0, 0)".parse::<Color>().unwrap(), red);
+        assert_eq!("hsl(360, 100%, 50%)".parse::<Color>().unwrap(), red);
+        assert_eq!(
+            "hsl(120, 50%, 75%)".parse::<Color>().unwrap(),
+            Color(0x55_80_80)
+        );
+        assert_eq!(" Red ".parse::<Color>().unwrap(), red);
+        // Darker than DDNet can show
+        assert_eq!("black".parse::<Color>().unwrap(), Color(0));
+
+        assert!("#ff00".parse::<Color>().is_err());
+        assert!("rgb(256, 0, 0)".parse::<Color>().is_err());
+        assert!("hsl(0, 101%, 0%)".parse::<Color>().is_err());
+        assert!("grey-ish".parse::<Color>().is_err());
+
+        // DDNet values are numbers in JSON
+        assert_eq!(
+            serde_json::from_str::<Color>("32133138").unwrap(),
+            Color(15355922)
+        );
+        assert_eq!(serde_json::from_str::<Color>("\"red\"").unwrap(), red);
+        assert!(serde_json::from_str::<Color>("-1").is_err());
+    }
+}
@@ -0,0 +1,219 @@
+use std::time::SystemTime;
+
+use ohkami::{FromRequest, IntoResponse, Request, Response, openapi};
+use sha2::{Digest, Sha256};
+
+use crate::{app::skin::SkinQuery, render::RENDER_VERSION, rsync::lock::LockMeta};
+
+/// Default `max-age` of `Cache-Control` in seconds, overridden by `SKIN_MAX_AGE`
+pub const DEFAULT_MAX_AGE: u64 = 60 * 60;
+
+/// HTTP caching validators of a rendered skin
+#[derive(Debug, Clone)]
+pub struct Validators {
+    /// Strong `ETag`, changes with the stored skin, the renderer and the normalized query
+    pub etag: String,
+    pub last_modified: Option<SystemTime>,
+    /// `max-age` of `Cache-Control` in seconds
+    pub max_age: u64,
+    /// Response depends on `Accept` header
+    pub negotiated: bool,
+}
+
+impl Validators {
+    /// `query` must be normalized, so equal renders share an `ETag`.
+    ///
+    /// `ETag` is SHA-256 of the stored skin version, [RENDER_VERSION] and the query as JSON, so
+    /// it stays the same across restarts, but not across renderer changes.
+    pub fn new(
+        meta: &LockMeta,
+        query: &SkinQuery,
+        max_age: u64,
+        negotiated: bool,
+    ) -> Self {
+        Self {
+            etag: etag(meta, query, RENDER_VERSION),
+            last_modified: meta.modified(),
+            max_age,
+            negotiated,
+        }
+    }
+
+    /// Client already has this version by `If-None-Match` or, without it, `If-Modified-Since`
+    pub fn is_fresh(
+        &self,
+        if_none_match: Option<&str>,
+        if_modified_since: Option<&str>,
+    ) -> bool {
+        if let Some(tags) = if_none_match {
+            return tags
+                .split(',')
+                .map(str::trim)
+                .any(|tag| tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == self.etag);
+        }
+        match (if_modified_since, self.last_modified) {
+            (Some(since), Some(modified)) => {
+                httpdate::parse_http_date(since).is_ok_and(|since| modified <= since)
+            }
+            _ => false,
+        }
+    }
+
+    fn apply(
+        &self,
+        res: &mut Response,
+    ) {
+        res.headers
+            .set()
+            .etag(self.etag.clone())
+            .cache_control(format!("public, max-age={}", self.max_age))
+            .last_modified(
+                self.last_modified
+                    .map(|modified| httpdate::fmt_http_date(modified).into()),
+            );
+        if self.negotiated {
+            res.headers.set().vary("Accept");
+        }
+    }
+}
+
+/// Request method is `HEAD`, so rendering can be skipped if the result isn't cached
+#[derive(Debug, Clone, Copy)]
+pub struct Head(pub bool);
+
+impl<'req> FromRequest<'req> for Head {
+    type Error = std::convert::Infallible;
+
+    fn from_request(req: &'req Request) -> Option<Result<Self, Self::Error>> {
+        Some(Ok(Self(req.method.isHEAD())))
+    }
+}
+
+/// Response with caching headers, `304 Not Modified` without body
+#[derive(Debug)]
+pub struct Cached<T> {
+    pub body: Option<T>,
+    pub validators: Validators,
+    /// Stored skin rendered instead of an unknown name, sent as `X-Skin-Fallback`
+    pub fallback: Option<String>,
+}
+
+impl<T: IntoResponse> IntoResponse for Cached<T> {
+    #[inline]
+    fn into_response(self) -> Response {
+        let mut res = match self.body {
+            Some(body) => body.into_response(),
+            None => Response::NotModified(),
+        };
+        self.validators.apply(&mut res);
+        if let Some(fallback) = self.fallback {
+            // Revalidated on every use, so the requested skin shows up once it's stored
+            res.headers
+                .set()
+                .cache_control("no-cache")
+                .x("X-Skin-Fallback", fallback);
+        }
+        res
+    }
+
+    fn openapi_responses() -> openapi::Responses {
+        T::openapi_responses().or(304, openapi::Response::when("Not Modified"))
+    }
+}
+
+/// Quoted SHA-256 of the stored skin version, `render_version` and `query`.
+///
+/// A synced skin file always comes with a new `ita`, so the lock entry stands for its content.
+fn etag(
+    meta: &LockMeta,
+    query: &SkinQuery,
+    render_version: u32,
+) -> String {
+    let query = serde_json::to_vec(query).expect("SkinQuery must serialize to JSON");
+    let digest = Sha256::new()
+        .chain_update(&meta.origin)
+        .chain_update([0])
+        .chain_update(&meta.ita)
+        .chain_update([0])
+        .chain_update(render_version.to_be_bytes())
+        .chain_update(query)
+        .finalize();
+    format!(
+        "\"{}\"",
+        digest
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect::<String>()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::skin::TeeOptions;
+
+    #[test]
+    fn conditional_request() {
+        let meta = LockMeta {
+            path: "/store/default.png".to_string(),
+            origin: "https://ddnet.org/skins/skin/default.png".to_string(),
+            ita: "09-Nov-2024 12:00".to_string(),
+        };
+        let modified = meta.modified().unwrap();
+        assert_eq!(
+            httpdate::fmt_http_date(modified),
+            "Sat, 09 Nov 2024 12:00:00 GMT"
+        );
+
+        let query = SkinQuery {
+            name: "default".to_string(),
+            ..Default::default()
+        };
+        let validators = Validators::new(&meta, &query, DEFAULT_MAX_AGE, false);
+        let etag = validators.etag.clone();
+        assert_eq!(etag, super::etag(&meta, &query, RENDER_VERSION));
+        // Renders change with the stored skin and the renderer, so do their ETags
+        let updated = LockMeta {
+            ita: "10-Nov-2024 12:00".to_string(),
+            ..meta.clone()
+        };
+        assert_ne!(
+            Validators::new(&updated, &query, DEFAULT_MAX_AGE, false).etag,
+            etag
+        );
+        assert_ne!(super::etag(&meta, &query, RENDER_VERSION + 1), etag);
+        assert!(validators.is_fresh(Some(&etag), None));
+        assert!(validators.is_fresh(Some(&format!("\"other\", W/{etag}")), None));
+        assert!(validators.is_fresh(Some("*"), None));
+        assert!(!validators.is_fresh(Some("\"other\""), Some("Sat, 09 Nov 2024 12:00:00 GMT")));
+        assert!(validators.is_fresh(None, Some("Sun, 10 Nov 2024 00:00:00 GMT")));
+        assert!(!validators.is_fresh(None, Some("Fri, 08 Nov 2024 00:00:00 GMT")));
+        assert!(!validators.is_fresh(None, Some("garbage")));
+
+        let flipped = SkinQuery {
+            tee: TeeOptions {
+                flip: true,
+                ..Default::default()
+            },
+            ..query
+        };
+        assert_ne!(
+            Validators::new(&meta, &flipped, DEFAULT_MAX_AGE, false).etag,
+            etag
+        );
+
+        let cached = |fallback: Option<&str>| {
+            Cached::<Response> {
+                body: None,
+                validators: validators.clone(),
+                fallback: fallback.map(str::to_string),
+            }
+            .into_response()
+        };
+        let res = cached(None);
+        assert_eq!(res.headers.cache_control(), Some("public, max-age=3600"));
+        let res = cached(Some("default"));
+        assert_eq!(res.headers.cache_control(), Some("no-cache"));
+        assert_eq!(res.headers.get("X-Skin-Fallback"), Some("default"));
+    }
+}
@@ -0,0 +1,386 @@
+use std::{fmt::Display, sync::Arc};
+
+use ohkami::{
+    Fang, FangProc, IntoResponse, Request, Response, Status,
+    claw::{
+        header::{IfModifiedSince, IfNoneMatch},
+        param::Path,
+    },
+    fang::Context,
+    openapi::{self, Schema, SchemaRef, operation},
+    serde::Deserialize,
+};
+use tracing::instrument;
+
+use crate::{
+    app::{
+        AppState,
+        color::Color,
+        conditional::{Cached, Head},
+        format::{Format, Image},
+        query::{FlatQuery, flatten},
+        skin::{PlayerOptions, RenderOptions, SKIN_PATH, SkinQuery, TeeOptions, cached_skin},
+    },
+    error::Error,
+};
+
+/// Path-style PNG render URL of a stored skin, like `/skin/default.png`
+pub fn render_url(name: &str) -> String {
+    let mut url = format!("{SKIN_PATH}/");
+    for b in name.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                url.push(b as char)
+            }
+            b => url.push_str(&format!("%{b:02X}")),
+        }
+    }
+    url.push_str(".png");
+    url
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Path-style skin URL, `/{name}.{ext}` or `/{name}/{body}/{feet}.{ext}` under [SKIN_PATH]
+pub struct FilePath {
+    pub name: String,
+    pub body: Option<Color>,
+    pub feet: Option<Color>,
+    pub format: Format,
+}
+
+impl FilePath {
+    /// Parse path segments after [SKIN_PATH]
+    pub fn parse(segments: &[&str]) -> Result<Self, Error> {
+        let (name, body, file) = match segments {
+            [file] => (None, None, file),
+            [name, body, file] => (Some(name), Some(body.parse()?), file),
+            _ => {
+                return Err(Error::InvalidQuery(
+                    "expected `/{name}.{ext}` or `/{name}/{body}/{feet}.{ext}`".to_string(),
+                ));
+            }
+        };
+        let (stem, extension) = file
+            .rsplit_once('.')
+            .filter(|(stem, _)| !stem.is_empty())
+            .ok_or_else(|| {
+                Error::InvalidQuery(format!("`{file}` has no file extension, like `{file}.png`"))
+            })?;
+        let format = Format::from_extension(extension).ok_or_else(|| {
+            Error::InvalidQuery(format!(
+                "unsupported file extension `{extension}`, expected `png`, `webp` or `jpg`"
+            ))
+        })?;
+        Ok(match name {
+            Some(name) => FilePath {
+                name: name.to_string(),
+                body,
+                feet: Some(stem.parse()?),
+                format,
+            },
+            None => FilePath {
+                name: stem.to_string(),
+                body: None,
+                feet: None,
+                format,
+            },
+        })
+    }
+
+    /// [FilePath] of a request routing missed, if it looks like one
+    fn of(req: &Request) -> Option<Result<Self, Error>> {
+        let path = req.path.str();
+        let segments = path
+            .strip_prefix(SKIN_PATH)?
+            .strip_prefix('/')?
+            .split('/')
+            .collect::<Vec<_>>();
+        let file_like = matches!(segments.len(), 1 | 3)
+            && segments.last().is_some_and(|file| file.contains('.'));
+        file_like.then(|| Self::parse(&segments))
+    }
+}
+
+#[derive(Clone)]
+/// Serves path-style URLs which can't be routed by [file_handler].
+///
+/// ohkami captures at most two path params and matches static segments by prefix, so
+/// `/{name}/{body}/{feet}.{ext}` and names like `rawr.png` under `/raw` end in its bare 404.
+/// Those are rendered here instead, so any skin name works.
+pub struct SkinFile;
+
+impl<I: FangProc> Fang<I> for SkinFile {
+    type Proc = SkinFileProc<I>;
+
+    fn chain(
+        &self,
+        inner: I,
+    ) -> Self::Proc {
+        SkinFileProc { inner }
+    }
+}
+
+pub struct SkinFileProc<I: FangProc> {
+    inner: I,
+}
+
+impl<I: FangProc> FangProc for SkinFileProc<I> {
+    async fn bite<'b>(
+        &'b self,
+        req: &'b mut Request,
+    ) -> Response {
+        let res = self.inner.bite(req).await;
+        // Handlers answer 404 with a body, routing without
+        if !((req.method.isGET() || req.method.isHEAD())
+            && res.status == Status::NotFound
+            && res.headers.content_type().is_none())
+        {
+            return res;
+        }
+        let Some(path) = FilePath::of(req) else {
+            return res;
+        };
+        let state = req
+            .context
+            .get::<Arc<AppState>>()
+            .expect("AppState is set by app");
+        match (path, FlatQuery::<FileQuery>::parse(req)) {
+            (Ok(path), Ok(FlatQuery(query))) => file_skin(
+                state,
+                query,
+                path,
+                req.method.isHEAD(),
+                req.headers.if_none_match(),
+                req.headers.if_modified_since(),
+            )
+            .await
+            .into_response(),
+            (Err(e), _) | (_, Err(e)) => e.into_response(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+/// Options of path-style skin URLs, name, colors and format are in the path
+pub struct FileQuery {
+    /// How the tee is drawn
+    #[serde(flatten)]
+    pub tee: TeeOptions,
+    /// Which stored skin is drawn
+    #[serde(flatten)]
+    pub player: PlayerOptions,
+    /// Output image options
+    #[serde(flatten)]
+    pub options: RenderOptions,
+}
+
+impl FileQuery {
+    /// [SkinQuery] equal to the query-string form of the same URL, so both share the cache
+    pub fn skin(
+        self,
+        path: FilePath,
+    ) -> SkinQuery {
+        SkinQuery {
+            name: path.name,
+            body: path.body,
+            feet: path.feet,
+            format: Some(path.format),
+            tee: self.tee,
+            player: self.player,
+            options: self.options,
+            ..Default::default()
+        }
+    }
+}
+
+impl Schema for FileQuery {
+    fn schema() -> impl Into<SchemaRef> {
+        openapi::component(
+            "FileQuery",
+            flatten::<RenderOptions>(flatten::<PlayerOptions>(flatten::<TeeOptions>(
+                openapi::object(),
+            ))),
+        )
+    }
+}
+
+impl Display for FileQuery {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        f.debug_struct("FileQuery")
+            .field("tee", &self.tee)
+            .field("player", &self.player)
+            .field("options", &self.options)
+            .finish()
+    }
+}
+
+/// Render [FilePath] with options, the same as `GET /skin` with `format` of the extension
+async fn file_skin(
+    state: &AppState,
+    query: FileQuery,
+    path: FilePath,
+    head: bool,
+    if_none_match: Option<&str>,
+    if_modified_since: Option<&str>,
+) -> Result<Cached<Image>, Error> {
+    let mut query = query.skin(path);
+    query.validate()?;
+    cached_skin(state, query, false, head, if_none_match, if_modified_since).await
+}
+
+#[inline(always)]
+#[instrument(skip_all, level="info", fields(file=%file, size=?query.tee.size))]
+#[operation({
+    summary: "Get rendered skin image by file name",
+})]
+/// Represent GET method of `/{name}.{ext}`, the same as `GET /skin?name=` with `format` of the extension.
+///
+/// For CDNs and embeds which strip query strings. `/{name}/{body}/{feet}.{ext}` sets colors too.
+pub async fn file_handler(
+    Context(state): Context<'_, Arc<AppState>>,
+    Path(file): Path<String>,
+    FlatQuery(query): FlatQuery<FileQuery>,
+    Head(head): Head,
+    if_none_match: Option<IfNoneMatch<&str>>,
+    if_modified_since: Option<IfModifiedSince<&str>>,
+) -> Result<Cached<Image>, Error> {
+    file_skin(
+        state,
+        query,
+        FilePath::parse(&[&file])?,
+        head,
+        if_none_match.map(|IfNoneMatch(tags)| tags),
+        if_modified_since.map(|IfModifiedSince(since)| since),
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use dashmap::DashMap;
+    use image::RgbaImage;
+    use ohkami::{Ohkami, Route, testing::*};
+
+    use super::*;
+    use crate::{
+        app::skin::skin_router,
+        cache::CacheStore,
+        remote::{DEFAULT_MAX_SIZE, RemoteStore},
+        rsync::lock::{DEFAULT_FROZEN_SKIN, LockMeta, LockStore},
+    };
+
+    #[test]
+    fn file_paths() {
+        let path = FilePath::parse(&["default.png"]).unwrap();
+        assert_eq!((path.name.as_str(), path.format), ("default", Format::Png));
+        let path = FilePath::parse(&["v1.2.WEBP"]).unwrap();
+        assert_eq!((path.name.as_str(), path.format), ("v1.2", Format::Webp));
+        let path = FilePath::parse(&["default", "65280", "#ff8800.jpg"]).unwrap();
+        assert_eq!(path.body, Some(Color(65280)));
+        assert_eq!(path.feet, Some("#ff8800".parse().unwrap()));
+        assert_eq!(path.format, Format::Jpeg);
+
+        assert!(FilePath::parse(&["default"]).is_err());
+        assert!(FilePath::parse(&[".png"]).is_err());
+        assert!(FilePath::parse(&["default.gif"]).is_err());
+        assert!(FilePath::parse(&["default", "zz", "red.png"]).is_err());
+        assert!(FilePath::parse(&["default", "red.png"]).is_err());
+
+        assert_eq!(render_url("Big Ninja #2"), "/skin/Big%20Ninja%20%232.png");
+    }
+
+    /// Store of `default` and `rawr.png`, whose name starts like the `/raw` route
+    async fn test_app() -> TestOhkami {
+        let dir = std::env::temp_dir().join("cdn-file-routing");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("default.png");
+        RgbaImage::new(256, 128).save(&path).unwrap();
+        let meta = LockMeta {
+            path: path.to_string_lossy().to_string(),
+            origin: "https://ddnet.org/skins/skin/default.png".to_string(),
+            ita: "09-Nov-2024 12:00".to_string(),
+        };
+        let store = |skins: Vec<(String, LockMeta)>| {
+            Arc::new(LockStore {
+                path: dir.clone(),
+                store: Arc::new(DashMap::from_iter(skins)),
+                recent: Default::default(),
+                frozen_skin: DEFAULT_FROZEN_SKIN.to_string(),
+                fallback_skin: None,
+            })
+        };
+        Ohkami::new((
+            Context::new(Arc::new(AppState {
+                lock: store(vec![
+                    ("default".to_string(), meta.clone()),
+                    ("rawr.png".to_string(), meta),
+                ]),
+                seven: store(Vec::new()),
+                cache: Arc::new(CacheStore::new().await),
+                remote: Arc::new(RemoteStore::new(
+                    Vec::new(),
+                    DEFAULT_MAX_SIZE,
+                    Duration::from_secs(1),
+                    Duration::from_secs(1),
+                )),
+                max_age: 60,
+            })),
+            SKIN_PATH.By(skin_router()),
+        ))
+        .test()
+    }
+
+    #[tokio::test]
+    async fn file_routing() {
+        let t = test_app().await;
+
+        for path in [
+            "/skin/default.png",
+            "/skin/default.webp?size=64&padding=4",
+            "/skin/default/red/%23ff8800.jpg",
+            "/skin/rawr.png.png",
+            "/skin/rawr.png/red/blue.webp?size=64&angle=45&flip=true&shadow=true",
+        ] {
+            let res = t.oneshot(TestRequest::GET(path)).await;
+            assert_eq!(res.status(), Status::OK, "{path}");
+            assert!(res.header("Content-Type").unwrap().starts_with("image/"));
+        }
+        let res = t
+            .oneshot(TestRequest::HEAD("/skin/default/red/blue.png"))
+            .await;
+        assert_eq!(res.status(), Status::OK);
+
+        // Unknown skins and bad paths are answered by the fallback, not ohkami's bare 404
+        for (path, status) in [
+            ("/skin/nope.png", Status::NotFound),
+            ("/skin/nope/red/blue.png", Status::NotFound),
+            ("/skin/default.gif", Status::BadRequest),
+            ("/skin/default/zz/blue.png", Status::BadRequest),
+            ("/skin/default.png?size=nope", Status::BadRequest),
+            ("/skin/default/red/blue.png?angle=nope", Status::BadRequest),
+        ] {
+            let res = t.oneshot(TestRequest::GET(path)).await;
+            assert_eq!(res.status(), status, "{path}");
+            assert_eq!(
+                res.header("Content-Type"),
+                Some("application/json"),
+                "{path}"
+            );
+        }
+        // Paths which don't look like files keep the bare 404
+        for path in [
+            "/skin/a/b.png",
+            "/skin/a/b/c/d.png",
+            "/skin/default/red/blue",
+        ] {
+            let res = t.oneshot(TestRequest::GET(path)).await;
+            assert_eq!(res.status(), Status::NotFound, "{path}");
+            assert_eq!(res.header("Content-Type"), None, "{path}");
+        }
+    }
+}
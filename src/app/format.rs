@@ -0,0 +1,198 @@
+use ohkami::{
+    IntoResponse, Response,
+    claw::{content::IntoContent, status::OK},
+    openapi,
+    serde::Deserialize,
+};
+use serde::Serialize;
+
+use crate::app::{jpeg::Jpeg, png::Png, webp::WebP};
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, Hash, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+/// Output image format
+pub enum Format {
+    #[default]
+    Png,
+    /// Lossless WebP
+    Webp,
+    /// JPEG flattened on a background, as it has no alpha channel
+    Jpeg,
+}
+
+impl Format {
+    pub const fn mime(&self) -> &'static str {
+        match self {
+            Format::Png => "image/png",
+            Format::Webp => "image/webp",
+            Format::Jpeg => "image/jpeg",
+        }
+    }
+
+    pub const fn extension(&self) -> &'static str {
+        match self {
+            Format::Png => "png",
+            Format::Webp => "webp",
+            Format::Jpeg => "jpg",
+        }
+    }
+
+    /// Format of a file extension, `jpg` and `jpeg` are both JPEG
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "png" => Some(Format::Png),
+            "webp" => Some(Format::Webp),
+            "jpg" | "jpeg" => Some(Format::Jpeg),
+            _ => None,
+        }
+    }
+
+    /// Pick the best supported format from `Accept` header value.
+    ///
+    /// Wildcards resolve to [Format::Png] and lose to explicit types with
+    /// the same weight, so only clients which ask for WebP or JPEG get them.
+    pub fn from_accept(accept: &str) -> Option<Self> {
+        accept
+            .split(',')
+            .filter_map(|item| {
+                let mut params = item.split(';').map(str::trim);
+                let (format, explicit) = match params.next()? {
+                    "image/png" => (Format::Png, true),
+                    "image/webp" => (Format::Webp, true),
+                    "image/jpeg" => (Format::Jpeg, true),
+                    "image/*" | "*/*" => (Format::Png, false),
+                    _ => return None,
+                };
+                let q = params
+                    .find_map(|param| param.strip_prefix("q="))
+                    .map_or(Some(1.), |q| q.parse::<f32>().ok())?;
+                (q > 0.).then_some((format, q, explicit))
+            })
+            .max_by(|(_, a_q, a_explicit), (_, b_q, b_explicit)| {
+                a_q.total_cmp(b_q).then(a_explicit.cmp(b_explicit))
+            })
+            .map(|(format, ..)| format)
+    }
+}
+
+/// Rendered image in one of supported [Format]s
+#[derive(Debug)]
+pub struct Image {
+    pub format: Format,
+    /// `None` answers `HEAD` of an image which isn't rendered, without `Content-Length`
+    pub data: Option<Vec<u8>>,
+    /// [Format] was negotiated by `Accept` header
+    pub negotiated: bool,
+}
+
+impl Image {
+    pub fn new(
+        format: Format,
+        data: Vec<u8>,
+    ) -> Self {
+        Self {
+            format,
+            data: Some(data),
+            negotiated: false,
+        }
+    }
+
+    /// Headers of an image which isn't rendered yet
+    pub fn head(format: Format) -> Self {
+        Self {
+            format,
+            data: None,
+            negotiated: false,
+        }
+    }
+}
+
+impl IntoResponse for Image {
+    #[inline]
+    fn into_response(self) -> Response {
+        let mut res = match (self.format, self.data) {
+            (Format::Png, Some(data)) => OK(Png(data)).into_response(),
+            (Format::Webp, Some(data)) => OK(WebP(data)).into_response(),
+            (Format::Jpeg, Some(data)) => OK(Jpeg(data)).into_response(),
+            (format, None) => unrendered(format.mime()),
+        };
+        if self.negotiated {
+            res.headers.set().vary("Accept");
+        }
+        res
+    }
+
+    fn openapi_responses() -> openapi::Responses {
+        openapi::Responses::new([(
+            200,
+            openapi::Response::when("OK")
+                .content(Format::Png.mime(), Png::openapi_responsebody())
+                .content(Format::Webp.mime(), WebP::openapi_responsebody())
+                .content(Format::Jpeg.mime(), Jpeg::openapi_responsebody()),
+        )])
+    }
+}
+
+/// Content of a single type like [OK], `None` answers `HEAD` like [Image::head]
+#[derive(Debug)]
+pub struct Headed<T>(pub Option<T>);
+
+impl<T: IntoContent> IntoResponse for Headed<T> {
+    #[inline]
+    fn into_response(self) -> Response {
+        match self.0 {
+            Some(content) => OK(content).into_response(),
+            None => unrendered(T::CONTENT_TYPE),
+        }
+    }
+
+    fn openapi_responses() -> openapi::Responses {
+        OK::<T>::openapi_responses()
+    }
+}
+
+/// `200 OK` of content which isn't rendered yet, answering `HEAD`
+pub fn unrendered(content_type: &'static str) -> Response {
+    // Length is unknown until rendering, so not the default `0`
+    Response::OK().with_headers(|h| h.content_type(content_type).content_length(None))
+}
+
+#[cfg(test)]
+mod tests {
+    use ohkami::IntoResponse;
+
+    use crate::app::{
+        format::{Format, Headed, Image},
+        svg::Svg,
+    };
+
+    #[test]
+    fn head_image() {
+        let res = Image::head(Format::Webp).into_response();
+        assert_eq!(res.headers.content_type(), Some("image/webp"));
+        assert_eq!(res.headers.content_length(), None);
+        let res = Image::new(Format::Png, vec![0; 42]).into_response();
+        assert_eq!(res.headers.content_length(), Some("42"));
+        let res = Headed::<Svg>(None).into_response();
+        assert_eq!(res.headers.content_type(), Some("image/svg+xml"));
+        assert_eq!(res.headers.content_length(), None);
+    }
+
+    #[test]
+    fn accept_negotiation() {
+        assert_eq!(
+            Format::from_accept("image/avif,image/webp,*/*;q=0.8"),
+            Some(Format::Webp)
+        );
+        assert_eq!(
+            Format::from_accept("image/jpeg;q=0.5, image/png"),
+            Some(Format::Png)
+        );
+        assert_eq!(Format::from_accept("*/*, image/jpeg"), Some(Format::Jpeg));
+        assert_eq!(
+            Format::from_accept("image/webp;q=0, */*"),
+            Some(Format::Png)
+        );
+        assert_eq!(Format::from_accept("text/html"), None);
+    }
+}
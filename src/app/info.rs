@@ -0,0 +1,183 @@
+use std::{fmt::Display, sync::Arc};
+
+use ohkami::{
+    Json, Query,
+    fang::Context,
+    openapi::{self, Schema, SchemaRef, operation},
+    serde::Deserialize,
+};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tokio::task::spawn_blocking;
+use tracing::instrument;
+
+use crate::{
+    app::{AppState, color::Rgb, file::render_url},
+    error::Error,
+    render,
+    rsync::lock::Source,
+};
+
+#[derive(Debug, Clone, Deserialize)]
+/// Stored skin info query
+pub struct InfoQuery {
+    /// **note**: Replace all whitespaces to `_`
+    pub name: String,
+}
+
+impl Schema for InfoQuery {
+    fn schema() -> impl Into<SchemaRef> {
+        openapi::component(
+            "InfoQuery",
+            openapi::object().property(
+                "name",
+                openapi::string()
+                    .format("a-zA-Z0-9_")
+                    .description("Skin name")
+                    .example("default"),
+            ),
+        )
+    }
+}
+
+impl Display for InfoQuery {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        f.debug_struct("InfoQuery")
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+#[derive(Debug, Serialize)]
+/// Metadata of a stored skin
+pub struct SkinInfo {
+    pub name: String,
+    /// Download link
+    pub origin: String,
+    /// Last update timestamp of the skin list
+    pub ita: String,
+    /// `ita` as unix time in seconds
+    pub updated: Option<u64>,
+    pub source: Source,
+    pub width: u32,
+    pub height: u32,
+    /// File size in bytes
+    pub size: usize,
+    /// Hex SHA-256 of the file
+    pub sha256: String,
+    /// Dominant color of the body, `None` if it's empty
+    pub body: Option<Rgb>,
+    /// Dominant color of the feet, `None` if they're empty
+    pub feet: Option<Rgb>,
+    /// Path-style PNG render URL
+    pub url: String,
+}
+
+impl Schema for SkinInfo {
+    fn schema() -> impl Into<SchemaRef> {
+        openapi::component(
+            "SkinInfo",
+            openapi::object()
+                .property("name", openapi::string())
+                .property("origin", openapi::string().description("Download link"))
+                .property(
+                    "ita",
+                    openapi::string()
+                        .description("Last update timestamp of the skin list")
+                        .example("09-Nov-2024 12:00"),
+                )
+                .property(
+                    "updated",
+                    openapi::integer()
+                        .description("`ita` as unix time in seconds")
+                        .nullable(),
+                )
+                .property("source", Source::schema())
+                .property("width", openapi::integer())
+                .property("height", openapi::integer())
+                .property("size", openapi::integer().description("File size in bytes"))
+                .property(
+                    "sha256",
+                    openapi::string().description("Hex SHA-256 of the file"),
+                )
+                .property(
+                    "body",
+                    openapi::string()
+                        .description("Dominant color of the body as `#rrggbb`")
+                        .example("#ff8800")
+                        .nullable(),
+                )
+                .property(
+                    "feet",
+                    openapi::string()
+                        .description("Dominant color of the feet as `#rrggbb`")
+                        .example("#ff8800")
+                        .nullable(),
+                )
+                .property(
+                    "url",
+                    openapi::string()
+                        .description("Path-style PNG render URL")
+                        .example("/skin/default.png"),
+                ),
+        )
+    }
+}
+
+#[inline(always)]
+#[instrument(skip_all, level="info", fields(name=%query.name))]
+#[operation({
+    summary: "Get metadata of a stored skin",
+})]
+/// Represent GET method to return where a stored skin came from, when it was updated and
+/// what its file looks like
+pub async fn info_handler(
+    Context(state): Context<'_, Arc<AppState>>,
+    Query(query): Query<InfoQuery>,
+) -> Result<Json<SkinInfo>, Error> {
+    let meta = state
+        .lock
+        .store
+        .get(&query.name)
+        .map(|x| x.value().clone())
+        .ok_or_else(|| state.lock.not_found(&query.name))?;
+    let data = state.lock.uv(&query.name).await?;
+    let size = data.len();
+    let name = query.name.clone();
+
+    let ((width, height), sha256, (body, feet)) = spawn_blocking(
+        #[inline]
+        move || {
+            let sha256 = Sha256::digest(&data)
+                .iter()
+                .map(|b| format!("{b:02x}"))
+                .collect::<String>();
+            let img = image::load_from_memory(&data)
+                .map_err(|e| {
+                    Error::InvalidSkin(format!("stored skin `{name}` can't be decoded: {e}"))
+                })?
+                .into_rgba8();
+            let colors = render::info::colors(&img);
+            Ok::<_, Error>((img.dimensions(), sha256, colors))
+        },
+    )
+    .await??;
+
+    Ok(Json(SkinInfo {
+        url: render_url(&query.name),
+        updated: meta.updated(),
+        source: meta.source(),
+        width,
+        height,
+        size,
+        sha256,
+        body: body.map(Rgb),
+        feet: feet.map(Rgb),
+        name: query.name,
+        origin: meta.origin,
+        ita: meta.ita,
+    }))
+}
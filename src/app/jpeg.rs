@@ -0,0 +1,25 @@
+use std::borrow::Cow;
+
+use ohkami::{
+    claw::content::IntoContent,
+    openapi::{self, Schema},
+};
+
+/// Represents a JPEG image content.
+#[derive(Debug, Schema)]
+#[openapi(component)]
+pub struct Jpeg(pub Vec<u8>);
+
+impl IntoContent for Jpeg {
+    const CONTENT_TYPE: &'static str = "image/jpeg";
+
+    #[inline(always)]
+    fn into_content(self) -> Result<std::borrow::Cow<'static, [u8]>, impl std::fmt::Display> {
+        Result::<_, std::convert::Infallible>::Ok(Cow::Owned(self.0))
+    }
+
+    #[inline(always)]
+    fn openapi_responsebody() -> impl Into<openapi::schema::SchemaRef> {
+        openapi::string().format("binary")
+    }
+}
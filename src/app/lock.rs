@@ -1,20 +1,390 @@
-use std::sync::Arc;
+use std::{cmp::Ordering, fmt::Display, sync::Arc};
 
-use ohkami::{Json, fang::Context};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use ohkami::{
+    Json, Query,
+    fang::Context,
+    openapi::{self, Schema, SchemaRef, operation},
+    serde::Deserialize,
+};
+use regex::{Regex, RegexBuilder};
+use serde::Serialize;
+use tracing::instrument;
 
-use crate::{app::AppState, error::Error};
+use crate::{
+    app::{AppState, file::render_url},
+    error::Error,
+    rsync::lock::Source,
+};
+
+/// Upper bound for skins of a catalog page
+pub const MAX_LIMIT: usize = 1000;
+/// Upper bound for compiled `regex` filter in bytes
+const REGEX_SIZE_LIMIT: usize = 1 << 20;
+
+fn default_limit() -> usize {
+    100
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+/// Sort key of a catalog
+pub enum Sort {
+    #[default]
+    Name,
+    /// Last update, skins without one first
+    Updated,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Order {
+    #[default]
+    Asc,
+    Desc,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+/// Stored skins catalog query, all filters must match
+pub struct CatalogQuery {
+    /// Names containing it, ignoring case
+    pub q: Option<String>,
+    /// Names starting with it
+    pub prefix: Option<String>,
+    /// Names matching the regular expression
+    pub regex: Option<String>,
+    pub source: Option<Source>,
+    /// Updated at or after unix time in seconds
+    pub since: Option<u64>,
+    #[serde(default)]
+    pub sort: Sort,
+    #[serde(default)]
+    pub order: Order,
+    /// `next` of the previous page
+    pub cursor: Option<String>,
+    /// Skins per page
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Position after the last skin of a page, by both sort keys
+struct Cursor {
+    updated: Option<u64>,
+    name: String,
+}
+
+impl Cursor {
+    fn encode(&self) -> String {
+        let updated = self.updated.map(|x| x.to_string()).unwrap_or_default();
+        URL_SAFE_NO_PAD.encode(format!("{updated}/{}", self.name))
+    }
+
+    fn decode(cursor: &str) -> Result<Self, Error> {
+        let invalid = || Error::InvalidQuery(format!("invalid cursor `{cursor}`"));
+        let cursor = URL_SAFE_NO_PAD.decode(cursor).map_err(|_| invalid())?;
+        let cursor = String::from_utf8(cursor).map_err(|_| invalid())?;
+        let (updated, name) = cursor.split_once('/').ok_or_else(invalid)?;
+        Ok(Cursor {
+            updated: match updated {
+                "" => None,
+                updated => Some(updated.parse().map_err(|_| invalid())?),
+            },
+            name: name.to_string(),
+        })
+    }
+}
+
+impl CatalogQuery {
+    /// Whether no filter or pagination is asked for, so all names are listed as before paging
+    fn is_listing(&self) -> bool {
+        self.q.is_none()
+            && self.prefix.is_none()
+            && self.regex.is_none()
+            && self.source.is_none()
+            && self.since.is_none()
+            && self.cursor.is_none()
+            && self.limit.is_none()
+    }
+
+    fn limit(&self) -> usize {
+        self.limit.unwrap_or_else(default_limit)
+    }
+
+    /// Check query values which can't be expressed by types and compile `regex`
+    fn validate(&self) -> Result<Option<Regex>, Error> {
+        if !(1..=MAX_LIMIT).contains(&self.limit()) {
+            return Err(Error::InvalidQuery(format!(
+                "limit must be in range 1..={MAX_LIMIT}, got {}",
+                self.limit()
+            )));
+        }
+        self.regex
+            .as_deref()
+            .map(|regex| {
+                RegexBuilder::new(regex)
+                    .size_limit(REGEX_SIZE_LIMIT)
+                    .build()
+                    .map_err(|e| Error::InvalidQuery(format!("invalid regex: {e}")))
+            })
+            .transpose()
+    }
+
+    /// Order of skins by `sort` and `order`, names break ties
+    fn cmp(
+        &self,
+        a: &Cursor,
+        b: &Cursor,
+    ) -> Ordering {
+        let ordering = match self.sort {
+            Sort::Name => a.name.cmp(&b.name),
+            Sort::Updated => a.updated.cmp(&b.updated).then(a.name.cmp(&b.name)),
+        };
+        match self.order {
+            Order::Asc => ordering,
+            Order::Desc => ordering.reverse(),
+        }
+    }
+}
+
+impl Schema for CatalogQuery {
+    fn schema() -> impl Into<SchemaRef> {
+        openapi::component(
+            "CatalogQuery",
+            openapi::object()
+                .optional(
+                    "q",
+                    openapi::string()
+                        .description("Names containing it, ignoring case")
+                        .example("ninja")
+                        .nullable(),
+                )
+                .optional(
+                    "prefix",
+                    openapi::string()
+                        .description("Names starting with it")
+                        .example("x_")
+                        .nullable(),
+                )
+                .optional(
+                    "regex",
+                    openapi::string()
+                        .description("Names matching the regular expression")
+                        .example("^(red|blue)")
+                        .nullable(),
+                )
+                .optional("source", Source::schema())
+                .optional(
+                    "since",
+                    openapi::integer()
+                        .description("Updated at or after unix time in seconds")
+                        .example(1731153600)
+                        .nullable(),
+                )
+                .optional(
+                    "sort",
+                    openapi::string()
+                        .description("`updated` puts skins without update time first")
+                        .enumerates(["name", "updated"])
+                        .default("name"),
+                )
+                .optional(
+                    "order",
+                    openapi::string().enumerates(["asc", "desc"]).default("asc"),
+                )
+                .optional(
+                    "cursor",
+                    openapi::string()
+                        .description("`next` of the previous page")
+                        .nullable(),
+                )
+                .optional(
+                    "limit",
+                    openapi::integer()
+                        .description("Skins per page, a page is returned when given")
+                        .minimum(1)
+                        .maximum(MAX_LIMIT as i32)
+                        .default(default_limit() as i32),
+                ),
+        )
+    }
+}
+
+impl Display for CatalogQuery {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        f.debug_struct("CatalogQuery")
+            .field("q", &self.q)
+            .field("prefix", &self.prefix)
+            .field("regex", &self.regex)
+            .field("source", &self.source)
+            .field("since", &self.since)
+            .field("sort", &self.sort)
+            .field("order", &self.order)
+            .field("cursor", &self.cursor)
+            .field("limit", &self.limit)
+            .finish()
+    }
+}
+
+#[derive(Debug, Serialize, Schema)]
+#[openapi(component)]
+/// Stored skin of a catalog
+pub struct CatalogSkin {
+    pub name: String,
+    /// Download link
+    pub origin: String,
+    pub source: Source,
+    /// Last update as unix time in seconds
+    pub updated: Option<u64>,
+    /// Path-style PNG render URL
+    pub url: String,
+}
+
+#[derive(Debug, Serialize, Schema)]
+#[openapi(component)]
+/// Page of stored skins
+pub struct Catalog {
+    /// Skins matching the filters on all pages
+    pub total: usize,
+    pub skins: Vec<CatalogSkin>,
+    /// `cursor` of the next page, if there is one
+    pub next: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+/// All stored skin names without filters or pagination, a [Catalog] page otherwise
+pub enum Store {
+    Names(Vec<String>),
+    Page(Catalog),
+}
+
+impl Schema for Store {
+    fn schema() -> impl Into<SchemaRef> {
+        openapi::component(
+            "Store",
+            openapi::one_of((openapi::array(openapi::string()), Catalog::schema())),
+        )
+    }
+}
 
 #[inline(always)]
-/// Represent GET method to return list of skins
+#[instrument(skip_all, level="info", fields(q=?query.q, prefix=?query.prefix, regex=?query.regex, sort=?query.sort, limit=?query.limit))]
+#[operation({
+    summary: "Search stored skins",
+})]
+/// Represent GET method to return a page of stored skins filtered and sorted by query.
+///
+/// Without filters or pagination it keeps returning all names as a plain array.
+/// Cursors point past the last skin of a page, so skins added during paging don't shift pages.
 pub async fn lock_handler(
-    Context(state): Context<'_, Arc<AppState>>
-) -> Result<Json<Vec<String>>, Error> {
-    Ok(Json(
-        state
-            .lock
-            .store
-            .iter()
-            .map(|x| x.key().to_string())
-            .collect::<Vec<_>>(),
-    ))
+    Context(state): Context<'_, Arc<AppState>>,
+    Query(query): Query<CatalogQuery>,
+) -> Result<Json<Store>, Error> {
+    let regex = query.validate()?;
+    let cursor = query.cursor.as_deref().map(Cursor::decode).transpose()?;
+    let q = query.q.as_deref().map(str::to_lowercase);
+
+    let mut skins = state
+        .lock
+        .store
+        .iter()
+        .filter(|x| {
+            let name = x.key();
+            q.as_ref()
+                .is_none_or(|q| name.to_lowercase().contains(q.as_str()))
+                && query
+                    .prefix
+                    .as_ref()
+                    .is_none_or(|prefix| name.starts_with(prefix.as_str()))
+                && regex.as_ref().is_none_or(|regex| regex.is_match(name))
+                && query
+                    .source
+                    .is_none_or(|source| x.value().source() == source)
+        })
+        .map(|x| {
+            (
+                Cursor {
+                    updated: x.value().updated(),
+                    name: x.key().clone(),
+                },
+                x.value().clone(),
+            )
+        })
+        .filter(|(key, _)| {
+            query
+                .since
+                .is_none_or(|since| key.updated.is_some_and(|updated| updated >= since))
+        })
+        .collect::<Vec<_>>();
+    skins.sort_by(|(a, _), (b, _)| query.cmp(a, b));
+    if query.is_listing() {
+        return Ok(Json(Store::Names(
+            skins.into_iter().map(|(key, _)| key.name).collect(),
+        )));
+    }
+
+    let total = skins.len();
+    let start = cursor.map_or(0, |cursor| {
+        skins.partition_point(|(key, _)| query.cmp(key, &cursor) != Ordering::Greater)
+    });
+    let end = total.min(start + query.limit());
+    let next = (end < total).then(|| skins[end - 1].0.encode());
+
+    Ok(Json(Store::Page(Catalog {
+        total,
+        skins: skins
+            .drain(start..end)
+            .map(|(key, meta)| CatalogSkin {
+                url: render_url(&key.name),
+                source: meta.source(),
+                origin: meta.origin,
+                updated: key.updated,
+                name: key.name,
+            })
+            .collect(),
+        next,
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn catalog_cursor() {
+        let cursor = Cursor {
+            updated: Some(1731153600),
+            name: "x/ninja".to_string(),
+        };
+        assert_eq!(Cursor::decode(&cursor.encode()).unwrap(), cursor);
+        let unknown = Cursor {
+            updated: None,
+            name: "default".to_string(),
+        };
+        assert_eq!(Cursor::decode(&unknown.encode()).unwrap(), unknown);
+        assert!(Cursor::decode("not a cursor").is_err());
+
+        let mut query = CatalogQuery {
+            q: None,
+            prefix: None,
+            regex: None,
+            source: None,
+            since: None,
+            sort: Sort::Updated,
+            order: Order::Desc,
+            cursor: None,
+            limit: None,
+        };
+        assert!(query.is_listing());
+        assert_eq!(query.cmp(&cursor, &unknown), Ordering::Less);
+        query.sort = Sort::Name;
+        assert_eq!(query.cmp(&cursor, &unknown), Ordering::Less);
+        query.order = Order::Asc;
+        assert_eq!(query.cmp(&cursor, &unknown), Ordering::Greater);
+        query.limit = Some(default_limit());
+        assert!(!query.is_listing());
+    }
 }
@@ -0,0 +1,122 @@
+use std::sync::Arc;
+
+use ohkami::{
+    fang::Context,
+    openapi::{self, Schema, SchemaRef, operation},
+    serde::Deserialize,
+};
+use serde::Serialize;
+use tracing::instrument;
+
+use crate::{
+    app::{
+        AppState,
+        color::Color,
+        conditional::Head,
+        format::Headed,
+        png::Png,
+        query::FlatQuery,
+        skin::{Eyes, Kind, SkinQuery, TeeOptions, cached_render},
+    },
+    error::Error,
+};
+
+#[derive(Debug, Clone, Deserialize)]
+/// Skin part query
+pub struct PartQuery {
+    /// **note**: Replace all whitespaces to `_`
+    pub name: String,
+    pub part: Part,
+    /// Body color
+    pub body: Option<Color>,
+    /// Feet color
+    pub feet: Option<Color>,
+    /// Eye expression for [Part::Eye]
+    #[serde(default)]
+    pub eyes: Eyes,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Hash, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Single part of the UV atlas
+pub enum Part {
+    Body,
+    BodyShadow,
+    Feet,
+    FeetShadow,
+    Hand,
+    HandShadow,
+    Eye,
+}
+
+impl From<PartQuery> for SkinQuery {
+    fn from(value: PartQuery) -> Self {
+        SkinQuery {
+            name: value.name,
+            body: value.body,
+            feet: value.feet,
+            tee: TeeOptions {
+                eyes: value.eyes,
+                ..Default::default()
+            },
+            kind: Kind::Part(value.part),
+            ..Default::default()
+        }
+    }
+}
+
+impl Schema for PartQuery {
+    fn schema() -> impl Into<SchemaRef> {
+        openapi::component(
+            "PartQuery",
+            openapi::object()
+                .property(
+                    "name",
+                    openapi::string()
+                        .format("a-zA-Z0-9_")
+                        .description("Skin name")
+                        .example("zzz"),
+                )
+                .property(
+                    "part",
+                    openapi::string()
+                        .description("Part of the UV atlas")
+                        .enumerates([
+                            "body",
+                            "body_shadow",
+                            "feet",
+                            "feet_shadow",
+                            "hand",
+                            "hand_shadow",
+                            "eye",
+                        ])
+                        .example("body"),
+                )
+                .optional("body", Color::schema())
+                .optional("feet", Color::schema())
+                .optional(
+                    "eyes",
+                    openapi::string()
+                        .description("Eye expression for `eye` part")
+                        .enumerates(["normal", "angry", "pain", "happy", "surprise", "blink"])
+                        .default("happy"),
+                ),
+        )
+    }
+}
+
+#[inline(always)]
+#[instrument(skip_all, level="info", fields(name=%query.name, part=?query.part, body=?query.body, feet=?query.feet))]
+#[operation({
+    summary: "Get single part of the skin",
+})]
+/// Represent GET method to return one part cropped from the UV atlas
+pub async fn part_handler(
+    Context(state): Context<'_, Arc<AppState>>,
+    FlatQuery(query): FlatQuery<PartQuery>,
+    Head(head): Head,
+) -> Result<Headed<Png>, Error> {
+    let mut query = SkinQuery::from(query);
+    query.normalize();
+    Ok(Headed(cached_render(state, query, head).await?.map(Png)))
+}
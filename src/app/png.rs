@@ -1,7 +1,7 @@
 use std::borrow::Cow;
 
 use ohkami::{
-    claw::content::IntoContent,
+    claw::content::{FromContent, IntoContent},
     openapi::{self, Schema},
 };
 
@@ -23,3 +23,17 @@ impl IntoContent for Png {
         openapi::string().format("binary")
     }
 }
+
+impl<'req> FromContent<'req> for Png {
+    const MIME_TYPE: &'static str = "image/png";
+
+    #[inline(always)]
+    fn from_content(body: &'req [u8]) -> Result<Self, impl std::fmt::Display> {
+        Result::<_, std::convert::Infallible>::Ok(Png(body.to_vec()))
+    }
+
+    #[inline(always)]
+    fn openapi_requestbody() -> impl Into<openapi::schema::SchemaRef> {
+        openapi::string().format("binary")
+    }
+}
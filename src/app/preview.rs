@@ -0,0 +1,125 @@
+use std::fmt::Display;
+
+use ohkami::{
+    claw::header::Accept,
+    openapi::{self, Schema, SchemaRef, operation},
+    serde::Deserialize,
+};
+use tokio::task::spawn_blocking;
+use tracing::instrument;
+
+use crate::{
+    app::{
+        color::Color,
+        format::{Format, Image},
+        png::Png,
+        query::{FlatQuery, flatten},
+        skin::{RenderOptions, SkinQuery, TeeOptions},
+    },
+    error::Error,
+    render,
+};
+
+#[derive(Debug, Clone, Deserialize)]
+/// Uploaded skin preview query
+pub struct PreviewQuery {
+    /// Body color
+    pub body: Option<Color>,
+    /// Feet color
+    pub feet: Option<Color>,
+    /// Output format, negotiated by `Accept` header if not set
+    pub format: Option<Format>,
+    /// How the tee is drawn
+    #[serde(flatten)]
+    pub tee: TeeOptions,
+    /// Output image options
+    #[serde(flatten)]
+    pub options: RenderOptions,
+}
+
+impl From<PreviewQuery> for SkinQuery {
+    fn from(value: PreviewQuery) -> Self {
+        SkinQuery {
+            body: value.body,
+            feet: value.feet,
+            format: value.format,
+            tee: value.tee,
+            options: value.options,
+            ..Default::default()
+        }
+    }
+}
+
+impl Schema for PreviewQuery {
+    fn schema() -> impl Into<SchemaRef> {
+        openapi::component(
+            "PreviewQuery",
+            flatten::<RenderOptions>(flatten::<TeeOptions>(
+                openapi::object()
+                    .optional("body", Color::schema())
+                    .optional("feet", Color::schema())
+                    .optional(
+                        "format",
+                        openapi::string()
+                            .description("Output format, negotiated by `Accept` header if not set")
+                            .enumerates(["png", "webp", "jpeg"])
+                            .nullable(),
+                    ),
+            )),
+        )
+    }
+}
+
+impl Display for PreviewQuery {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        f.debug_struct("PreviewQuery")
+            .field("body", &self.body)
+            .field("feet", &self.feet)
+            .field("format", &self.format)
+            .field("tee", &self.tee)
+            .field("options", &self.options)
+            .finish()
+    }
+}
+
+#[inline(always)]
+#[instrument(skip_all, level="info", fields(len=%uv.len(), body=?query.body, feet=?query.feet, eyes=?query.tee.eyes))]
+#[operation({
+    summary: "Render uploaded skin",
+})]
+/// Represent POST method to render an uploaded PNG UV atlas without storing or caching it.
+///
+/// Body size is bounded by `OHKAMI_REQUEST_PAYLOAD_LIMIT`.
+pub async fn preview_handler(
+    FlatQuery(query): FlatQuery<PreviewQuery>,
+    accept: Option<Accept<&str>>,
+    Png(uv): Png,
+) -> Result<Image, Error> {
+    let mut query = SkinQuery::from(query);
+    query.validate()?;
+    // Reject oversized uploads from their header, before decoding them
+    render::validate::check_header(&uv)?;
+    let negotiated = query.format.is_none();
+    let format = query
+        .format
+        .or_else(|| accept.and_then(|Accept(accept)| Format::from_accept(accept)))
+        .unwrap_or_default();
+    query.format = Some(format);
+    let data = spawn_blocking(
+        #[inline]
+        move || render::skin(uv, &query),
+    )
+    .await?
+    .map_err(|e| match e {
+        // Uploaded atlas is not a valid skin, unlike stored ones
+        Error::Tee(e) => Error::InvalidSkin(e.to_string()),
+        e => e,
+    })?;
+    Ok(Image {
+        negotiated,
+        ..Image::new(format, data.to_vec())
+    })
+}
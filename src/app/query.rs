@@ -0,0 +1,197 @@
+use std::{borrow::Cow, fmt::Display, str::FromStr};
+
+use ohkami::{
+    FromRequest, Request,
+    openapi::{self, Schema},
+};
+use serde::{
+    Deserialize, Deserializer,
+    de::{
+        self, DeserializeOwned, IntoDeserializer, Unexpected, Visitor,
+        value::{self, MapDeserializer},
+    },
+    forward_to_deserialize_any,
+};
+
+use crate::error::Error;
+
+/// Query params like [ohkami::Query], but also for types with `#[serde(flatten)]` fields,
+/// which the deserializer of [ohkami::Query] can't buffer.
+///
+/// Flattened fields are buffered as text, so ones which aren't strings need [parsed].
+pub struct FlatQuery<T>(pub T);
+
+impl<T: DeserializeOwned> FlatQuery<T> {
+    /// Query params of `req`, for fangs which can't take extractors
+    pub fn parse(req: &Request) -> Result<Self, Error> {
+        let params = req.query.iter().map(|(key, value)| (key, Param(value)));
+        T::deserialize(MapDeserializer::new(params))
+            .map(FlatQuery)
+            .map_err(|e: value::Error| Error::InvalidQuery(e.to_string()))
+    }
+}
+
+impl<'req, T: DeserializeOwned + Schema> FromRequest<'req> for FlatQuery<T> {
+    type Error = Error;
+
+    fn from_request(req: &'req Request) -> Option<Result<Self, Self::Error>> {
+        Some(Self::parse(req))
+    }
+
+    fn openapi_inbound() -> openapi::Inbound {
+        let Some(schema) = T::schema().into().into_inline() else {
+            return openapi::Inbound::None;
+        };
+        openapi::Inbound::Params(
+            schema
+                .into_properties()
+                .into_iter()
+                .map(|(name, schema, required)| match required {
+                    true => openapi::Parameter::in_query(name, schema),
+                    false => openapi::Parameter::in_query_optional(name, schema),
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Add properties of `T` to the schema of a query which flattens it
+pub fn flatten<T: Schema>(
+    object: openapi::schema::Schema<openapi::schema::Type::object>
+) -> openapi::schema::Schema<openapi::schema::Type::object> {
+    let properties = T::schema()
+        .into()
+        .into_inline()
+        .map(|schema| schema.into_properties())
+        .unwrap_or_default();
+    properties
+        .into_iter()
+        .fold(object, |object, (name, schema, _)| {
+            object.optional(name, schema)
+        })
+}
+
+/// Deserialize `T` as is or parse it from text, for fields flattened into [FlatQuery]
+/// which are also read from JSON
+pub fn parsed<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + FromStr,
+    T::Err: Display,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Parsed<T> {
+        Value(T),
+        Text(String),
+    }
+
+    match Parsed::<T>::deserialize(deserializer)? {
+        Parsed::Value(value) => Ok(value),
+        Parsed::Text(text) => text
+            .parse()
+            .map_err(|e| de::Error::custom(format!("invalid value `{text}`: {e}"))),
+    }
+}
+
+/// [parsed] for optional fields, `null` in JSON is `None`
+pub fn parsed_option<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + FromStr,
+    T::Err: Display,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Parsed<T> {
+        Value(Option<T>),
+        Text(String),
+    }
+
+    match Parsed::<T>::deserialize(deserializer)? {
+        Parsed::Value(value) => Ok(value),
+        Parsed::Text(text) => text
+            .parse()
+            .map(Some)
+            .map_err(|e| de::Error::custom(format!("invalid value `{text}`: {e}"))),
+    }
+}
+
+/// Percent decoded value of a query param, parsed by the type it's deserialized into
+struct Param<'q>(Cow<'q, str>);
+
+impl<'de> IntoDeserializer<'de, value::Error> for Param<'_> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+macro_rules! parse {
+    ($($method:ident => $visit:ident,)*) => {$(
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+            match self.0.parse() {
+                Ok(value) => visitor.$visit(value),
+                Err(_) => Err(de::Error::invalid_value(Unexpected::Str(&self.0), &visitor)),
+            }
+        }
+    )*};
+}
+
+impl<'de> Deserializer<'de> for Param<'_> {
+    type Error = value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Cow::Borrowed(value) => visitor.visit_str(value),
+            Cow::Owned(value) => visitor.visit_string(value),
+        }
+    }
+
+    parse! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_enum(IntoDeserializer::<Self::Error>::into_deserializer(self.0))
+    }
+
+    forward_to_deserialize_any! {
+        char str string bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
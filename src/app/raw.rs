@@ -0,0 +1,83 @@
+use std::sync::Arc;
+
+use ohkami::{
+    fang::Context,
+    openapi::{self, Schema, SchemaRef, operation},
+    serde::Deserialize,
+};
+use tracing::instrument;
+
+use crate::{
+    app::{
+        AppState,
+        color::Color,
+        conditional::Head,
+        format::Headed,
+        png::Png,
+        query::FlatQuery,
+        skin::{Kind, SkinQuery, cached_render},
+    },
+    error::Error,
+};
+
+#[derive(Debug, Clone, Deserialize)]
+/// Raw skin query
+pub struct RawQuery {
+    /// **note**: Replace all whitespaces to `_`
+    pub name: String,
+    /// Body color
+    pub body: Option<Color>,
+    /// Feet color
+    pub feet: Option<Color>,
+}
+
+impl From<RawQuery> for SkinQuery {
+    fn from(value: RawQuery) -> Self {
+        SkinQuery {
+            name: value.name,
+            body: value.body,
+            feet: value.feet,
+            kind: Kind::Atlas,
+            ..Default::default()
+        }
+    }
+}
+
+impl Schema for RawQuery {
+    fn schema() -> impl Into<SchemaRef> {
+        openapi::component(
+            "RawQuery",
+            openapi::object()
+                .property(
+                    "name",
+                    openapi::string()
+                        .format("a-zA-Z0-9_")
+                        .description("Skin name")
+                        .example("zzz"),
+                )
+                .optional("body", Color::schema())
+                .optional("feet", Color::schema()),
+        )
+    }
+}
+
+#[inline(always)]
+#[instrument(skip_all, level="info", fields(name=%query.name, body=?query.body, feet=?query.feet))]
+#[operation({
+    summary: "Get UV atlas of the skin",
+})]
+/// Represent GET method to return stored UV atlas, recolored if colors are set
+pub async fn raw_handler(
+    Context(state): Context<'_, Arc<AppState>>,
+    FlatQuery(query): FlatQuery<RawQuery>,
+    Head(head): Head,
+) -> Result<Headed<Png>, Error> {
+    let stored = query.body.is_none() && query.feet.is_none();
+    let query = SkinQuery::from(query);
+    let data = match stored {
+        true if head => state.lock.meta(&query).map(|_| None)?,
+        true => Some(state.lock.uv(&query.name).await?),
+        false => cached_render(state, query, head).await?,
+    };
+    Ok(Headed(data.map(Png)))
+}
@@ -0,0 +1,247 @@
+use std::{fmt::Display, sync::Arc};
+
+use ohkami::{
+    Json,
+    claw::header::Accept,
+    fang::Context,
+    openapi::{self, Schema, SchemaRef, operation},
+    serde::Deserialize,
+};
+use serde::Serialize;
+use tracing::instrument;
+
+use crate::{
+    app::{
+        AppState,
+        conditional::Head,
+        format::{Format, Image},
+        query::{FlatQuery, flatten},
+        skin::{Eyes, Kind, RenderOptions, SkinQuery, TeeOptions},
+    },
+    error::Error,
+};
+
+/// Name of the part used if it is not set
+pub const DEFAULT_PART: &str = "standard";
+
+fn default_part() -> String {
+    DEFAULT_PART.to_string()
+}
+
+#[derive(Debug, Clone, Deserialize)]
+/// Teeworlds 0.7 skin query
+pub struct SevenQuery {
+    /// Body part name
+    pub body: String,
+    /// Marking part name
+    pub marking: Option<String>,
+    /// Decoration part name
+    pub decoration: Option<String>,
+    /// Hands part name
+    #[serde(default = "default_part")]
+    pub hands: String,
+    /// Feet part name
+    #[serde(default = "default_part")]
+    pub feet: String,
+    /// Eyes part name
+    #[serde(default = "default_part")]
+    pub eyes: String,
+    /// Teeworlds 0.7 value
+    pub body_color: Option<i64>,
+    /// Teeworlds 0.7 value, including alpha
+    pub marking_color: Option<i64>,
+    /// Teeworlds 0.7 value
+    pub decoration_color: Option<i64>,
+    /// Teeworlds 0.7 value
+    pub hands_color: Option<i64>,
+    /// Teeworlds 0.7 value
+    pub feet_color: Option<i64>,
+    /// Teeworlds 0.7 value
+    pub eyes_color: Option<i64>,
+    /// Eye expression, `eyes` is the part
+    #[serde(default)]
+    pub emote: Eyes,
+    /// Output format, negotiated by `Accept` header if not set
+    pub format: Option<Format>,
+    /// How the tee is drawn, its `eyes` are set by `emote`
+    #[serde(flatten)]
+    pub tee: TeeOptions,
+    /// Output image options
+    #[serde(flatten)]
+    pub options: RenderOptions,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Hash, PartialEq, Eq, Serialize)]
+/// Teeworlds 0.7 parts of [SkinQuery], its body is [SkinQuery::name]
+pub struct Seven {
+    pub marking: Option<String>,
+    pub decoration: Option<String>,
+    pub hands: String,
+    pub feet: String,
+    pub eyes: String,
+    pub body_color: Option<u32>,
+    pub marking_color: Option<u32>,
+    pub decoration_color: Option<u32>,
+    pub hands_color: Option<u32>,
+    pub feet_color: Option<u32>,
+    pub eyes_color: Option<u32>,
+}
+
+impl TryFrom<SevenQuery> for SkinQuery {
+    type Error = Error;
+
+    fn try_from(value: SevenQuery) -> Result<Self, Self::Error> {
+        // Teeworlds keeps colors in signed integers, so alpha above 127 makes them negative
+        let color = |value: Option<i64>| value.map(|value| value as u32);
+        let mut query = SkinQuery {
+            name: value.body,
+            format: value.format,
+            tee: TeeOptions {
+                eyes: value.emote,
+                ..value.tee
+            },
+            options: value.options,
+            kind: Kind::Seven(Box::new(Seven {
+                marking: value.marking,
+                decoration: value.decoration,
+                hands: value.hands,
+                feet: value.feet,
+                eyes: value.eyes,
+                body_color: color(value.body_color),
+                marking_color: color(value.marking_color),
+                decoration_color: color(value.decoration_color),
+                hands_color: color(value.hands_color),
+                feet_color: color(value.feet_color),
+                eyes_color: color(value.eyes_color),
+            })),
+            ..Default::default()
+        };
+        query.validate()?;
+        Ok(query)
+    }
+}
+
+impl Schema for SevenQuery {
+    fn schema() -> impl Into<SchemaRef> {
+        let part = |description: &'static str| {
+            openapi::string()
+                .format("a-zA-Z0-9_")
+                .description(description)
+        };
+        let color = |description: &'static str| {
+            openapi::integer()
+                .description(description)
+                .example(65408)
+                .nullable()
+        };
+        openapi::component(
+            "SevenQuery",
+            // Own properties go last, so the `eyes` part replaces the flattened expression
+            flatten::<RenderOptions>(
+                flatten::<TeeOptions>(openapi::object())
+                    .property("body", part("Body part name").example("standard"))
+                    .optional("marking", part("Marking part name").nullable())
+                    .optional("decoration", part("Decoration part name").nullable())
+                    .optional("hands", part("Hands part name").default(DEFAULT_PART))
+                    .optional("feet", part("Feet part name").default(DEFAULT_PART))
+                    .optional("eyes", part("Eyes part name").default(DEFAULT_PART))
+                    .optional("body_color", color("Teeworlds 0.7 value"))
+                    .optional(
+                        "marking_color",
+                        color("Teeworlds 0.7 value, including alpha"),
+                    )
+                    .optional("decoration_color", color("Teeworlds 0.7 value"))
+                    .optional("hands_color", color("Teeworlds 0.7 value"))
+                    .optional("feet_color", color("Teeworlds 0.7 value"))
+                    .optional("eyes_color", color("Teeworlds 0.7 value"))
+                    .optional(
+                        "emote",
+                        openapi::string()
+                            .description("Eye expression")
+                            .enumerates(["normal", "angry", "pain", "happy", "surprise", "blink"])
+                            .default("happy"),
+                    )
+                    .optional(
+                        "format",
+                        openapi::string()
+                            .description("Output format, negotiated by `Accept` header if not set")
+                            .enumerates(["png", "webp", "jpeg"])
+                            .nullable(),
+                    ),
+            ),
+        )
+    }
+}
+
+impl Display for SevenQuery {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        f.debug_struct("SevenQuery")
+            .field("body", &self.body)
+            .field("marking", &self.marking)
+            .field("decoration", &self.decoration)
+            .field("hands", &self.hands)
+            .field("feet", &self.feet)
+            .field("eyes", &self.eyes)
+            .field("body_color", &self.body_color)
+            .field("marking_color", &self.marking_color)
+            .field("decoration_color", &self.decoration_color)
+            .field("hands_color", &self.hands_color)
+            .field("feet_color", &self.feet_color)
+            .field("eyes_color", &self.eyes_color)
+            .field("emote", &self.emote)
+            .field("format", &self.format)
+            .field("tee", &self.tee)
+            .field("options", &self.options)
+            .finish()
+    }
+}
+
+#[inline(always)]
+#[instrument(skip_all, level="info", fields(body=%query.body, marking=?query.marking, decoration=?query.decoration, feet=%query.feet, eyes=%query.eyes))]
+#[operation({
+    summary: "Get rendered Teeworlds 0.7 skin image",
+})]
+/// Represent GET method to return a tee composed from Teeworlds 0.7 parts
+pub async fn seven_handler(
+    Context(state): Context<'_, Arc<AppState>>,
+    FlatQuery(query): FlatQuery<SevenQuery>,
+    accept: Option<Accept<&str>>,
+    Head(head): Head,
+) -> Result<Image, Error> {
+    let mut query = SkinQuery::try_from(query)?;
+    let negotiated = query.format.is_none();
+    let format = query
+        .format
+        .or_else(|| accept.and_then(|Accept(accept)| Format::from_accept(accept)))
+        .unwrap_or_default();
+    // Negotiated format is a part of the cache key
+    query.format = Some(format);
+    let data = match state.cache.get(&query).await {
+        Ok(Some(e)) => Some(e.to_vec()),
+        _ if head => state.seven.check_seven(&query).map(|_| None)?,
+        _ => Some(state.seven.get_seven(state.cache.clone(), query).await?),
+    };
+    Ok(Image {
+        data,
+        negotiated,
+        ..Image::head(format)
+    })
+}
+
+#[inline(always)]
+/// Represent GET method to return list of Teeworlds 0.7 parts as `<part>/<name>`
+pub async fn seven_lock_handler(
+    Context(state): Context<'_, Arc<AppState>>
+) -> Result<Json<Vec<String>>, Error> {
+    Ok(Json(
+        state
+            .seven
+            .store
+            .iter()
+            .map(|x| x.key().to_string())
+            .collect::<Vec<_>>(),
+    ))
+}
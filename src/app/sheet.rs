@@ -0,0 +1,293 @@
+use std::{fmt::Display, sync::Arc};
+
+use futures::future;
+use ohkami::{
+    Query,
+    claw::header::Accept,
+    fang::Context,
+    openapi::{self, Schema, SchemaRef, operation},
+    serde::Deserialize,
+};
+use tokio::task::spawn_blocking;
+use tracing::{instrument, warn};
+
+use crate::{
+    app::{
+        AppState,
+        conditional::Head,
+        format::{Format, Image},
+        skin::{SkinQuery, TeeOptions},
+    },
+    error::Error,
+    render::{self, sheet},
+};
+
+/// Upper bound for tees on a sheet
+pub const MAX_TEES: usize = 256;
+/// Upper bound for columns of a sheet
+pub const MAX_COLUMNS: u32 = 32;
+/// Lower bound for cell width in pixels
+pub const MIN_CELL: u32 = 16;
+/// Upper bound for cell width in pixels
+pub const MAX_CELL: u32 = 256;
+
+fn default_columns() -> u32 {
+    8
+}
+
+fn default_cell() -> u32 {
+    96
+}
+
+fn default_page() -> usize {
+    1
+}
+
+fn default_per_page() -> usize {
+    64
+}
+
+#[derive(Debug, Clone, Deserialize)]
+/// Contact sheet query.
+///
+/// Skins are taken from one of `names`, `prefix` or `recent`, or from the whole store,
+/// then a `page` of them is rendered.
+pub struct SheetQuery {
+    /// Comma separated skin names, in order
+    pub names: Option<String>,
+    /// Stored skins starting with prefix
+    pub prefix: Option<String>,
+    /// Skins added by the last sync which added any
+    #[serde(default)]
+    pub recent: bool,
+    /// Page of skins, starting from 1
+    #[serde(default = "default_page")]
+    pub page: usize,
+    #[serde(default = "default_per_page")]
+    pub per_page: usize,
+    #[serde(default = "default_columns")]
+    pub columns: u32,
+    /// Cell width in pixels
+    #[serde(default = "default_cell")]
+    pub cell: u32,
+    /// Draw skin names under tees
+    #[serde(default)]
+    pub captions: bool,
+    /// Output format, negotiated by `Accept` header if not set
+    pub format: Option<Format>,
+}
+
+impl SheetQuery {
+    /// Check query values which can't be expressed by types
+    pub fn validate(&self) -> Result<(), Error> {
+        let sources = [self.names.is_some(), self.prefix.is_some(), self.recent];
+        if sources.into_iter().filter(|set| *set).count() > 1 {
+            return Err(Error::InvalidQuery(
+                "only one of names, prefix or recent can be set".to_string(),
+            ));
+        }
+        if self.page == 0 {
+            return Err(Error::InvalidQuery("page starts from 1".to_string()));
+        }
+        if !(1..=MAX_TEES).contains(&self.per_page) {
+            return Err(Error::InvalidQuery(format!(
+                "per_page must be in range 1..={MAX_TEES}, got {}",
+                self.per_page
+            )));
+        }
+        if !(1..=MAX_COLUMNS).contains(&self.columns) {
+            return Err(Error::InvalidQuery(format!(
+                "columns must be in range 1..={MAX_COLUMNS}, got {}",
+                self.columns
+            )));
+        }
+        if !(MIN_CELL..=MAX_CELL).contains(&self.cell) {
+            return Err(Error::InvalidQuery(format!(
+                "cell must be in range {MIN_CELL}..={MAX_CELL}, got {}",
+                self.cell
+            )));
+        }
+        Ok(())
+    }
+
+    /// Names of skins on the requested page
+    pub fn names(
+        &self,
+        state: &AppState,
+    ) -> Vec<String> {
+        let names = match (&self.names, &self.prefix) {
+            (Some(names), _) => names
+                .split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(str::to_string)
+                .collect(),
+            (_, Some(prefix)) => sorted(state.lock.store.iter().filter_map(|x| {
+                x.key()
+                    .starts_with(prefix.as_str())
+                    .then(|| x.key().clone())
+            })),
+            _ if self.recent => state.lock.recent.read().unwrap().clone(),
+            _ => sorted(state.lock.store.iter().map(|x| x.key().clone())),
+        };
+        names
+            .into_iter()
+            .skip((self.page - 1) * self.per_page)
+            .take(self.per_page)
+            .collect()
+    }
+}
+
+fn sorted(names: impl Iterator<Item = String>) -> Vec<String> {
+    let mut names = names.collect::<Vec<_>>();
+    names.sort();
+    names
+}
+
+impl Schema for SheetQuery {
+    fn schema() -> impl Into<SchemaRef> {
+        openapi::component(
+            "SheetQuery",
+            openapi::object()
+                .optional(
+                    "names",
+                    openapi::string()
+                        .description("Comma separated skin names, in order")
+                        .nullable(),
+                )
+                .optional(
+                    "prefix",
+                    openapi::string()
+                        .description("Stored skins starting with prefix")
+                        .nullable(),
+                )
+                .optional(
+                    "recent",
+                    openapi::bool()
+                        .description("Skins added by the last sync which added any")
+                        .default(false),
+                )
+                .optional(
+                    "page",
+                    openapi::integer()
+                        .description("Page of skins, starting from 1")
+                        .minimum(1)
+                        .default(default_page()),
+                )
+                .optional(
+                    "per_page",
+                    openapi::integer()
+                        .minimum(1)
+                        .maximum(MAX_TEES as i32)
+                        .default(default_per_page()),
+                )
+                .optional(
+                    "columns",
+                    openapi::integer()
+                        .minimum(1)
+                        .maximum(MAX_COLUMNS as i32)
+                        .default(default_columns()),
+                )
+                .optional(
+                    "cell",
+                    openapi::integer()
+                        .description("Cell width in pixels")
+                        .minimum(MIN_CELL as i32)
+                        .maximum(MAX_CELL as i32)
+                        .default(default_cell()),
+                )
+                .optional(
+                    "captions",
+                    openapi::bool()
+                        .description("Draw skin names under tees")
+                        .default(false),
+                )
+                .optional(
+                    "format",
+                    openapi::string()
+                        .description("Output format, negotiated by `Accept` header if not set")
+                        .enumerates(["png", "webp", "jpeg"])
+                        .nullable(),
+                ),
+        )
+    }
+}
+
+impl Display for SheetQuery {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        f.debug_struct("SheetQuery")
+            .field("names", &self.names)
+            .field("prefix", &self.prefix)
+            .field("recent", &self.recent)
+            .field("page", &self.page)
+            .field("per_page", &self.per_page)
+            .field("columns", &self.columns)
+            .field("cell", &self.cell)
+            .field("captions", &self.captions)
+            .field("format", &self.format)
+            .finish()
+    }
+}
+
+#[inline(always)]
+#[instrument(skip_all, level="info", fields(names=?query.names, prefix=?query.prefix, recent=%query.recent, page=%query.page))]
+#[operation({
+    summary: "Get grid image of many skins",
+})]
+/// Represent GET method to return a contact sheet of skins
+pub async fn sheet_handler(
+    Context(state): Context<'_, Arc<AppState>>,
+    Query(query): Query<SheetQuery>,
+    accept: Option<Accept<&str>>,
+    Head(head): Head,
+) -> Result<Image, Error> {
+    query.validate()?;
+    let negotiated = query.format.is_none();
+    let format = query
+        .format
+        .or_else(|| accept.and_then(|Accept(accept)| Format::from_accept(accept)))
+        .unwrap_or_default();
+    // Missing skins are left empty, so there is nothing to check
+    if head {
+        return Ok(Image {
+            negotiated,
+            ..Image::head(format)
+        });
+    }
+
+    let names = query.names(state);
+    let tees = future::join_all(names.into_iter().map(async |name| {
+        let skin = SkinQuery {
+            name: name.clone(),
+            format: Some(Format::Png),
+            tee: TeeOptions {
+                size: Some(query.cell),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let tee = match state.cache.get(&skin).await {
+            Ok(Some(e)) => Ok(e.to_vec()),
+            _ => state.lock.get(state.cache.clone(), skin).await,
+        };
+        if let Err(e) = &tee {
+            warn!(name=%name, error=?e, "Skin is left empty on the sheet");
+        }
+        (name, tee.ok())
+    }))
+    .await;
+
+    let (columns, cell, captions) = (query.columns, query.cell, query.captions);
+    let data = spawn_blocking(
+        #[inline]
+        move || render::encode(&sheet::sheet(&tees, columns, cell, captions)?, format),
+    )
+    .await??;
+    Ok(Image {
+        negotiated,
+        ..Image::new(format, data.to_vec())
+    })
+}
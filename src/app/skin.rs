@@ -1,66 +1,560 @@
 use std::{fmt::Display, sync::Arc};
 
+use image::imageops::FilterType;
 use ohkami::{
-    Ohkami, Query, Route,
-    claw::status::OK,
+    Ohkami, Route,
+    claw::header::{Accept, IfModifiedSince, IfNoneMatch},
     fang::Context,
     openapi::{self, Schema, SchemaRef, operation},
     serde::Deserialize,
 };
 use serde::Serialize;
+use tee_morphosis::tee::parts::EyeType;
 use tracing::instrument;
 
 use crate::{
-    app::{AppState, cache::cache_handler, lock::lock_handler, logger::LogRequest, png::Png},
+    app::{
+        AppState,
+        animated::{Animation, animated_handler},
+        batch::batch_handler,
+        cache::cache_handler,
+        card::card_handler,
+        color::{Color, Rgb},
+        conditional::{Cached, Head, Validators},
+        file::{SkinFile, file_handler},
+        format::{Format, Image},
+        info::info_handler,
+        lock::lock_handler,
+        logger::LogRequest,
+        part::{Part, part_handler},
+        preview::preview_handler,
+        query::{FlatQuery, flatten, parsed, parsed_option},
+        raw::raw_handler,
+        seven::{Seven, seven_handler, seven_lock_handler},
+        sheet::sheet_handler,
+        team::team_handler,
+        url::url_handler,
+        validate::{validate_handler, validate_store_handler},
+    },
     error::Error,
+    render::{MAX_PADDING, MAX_SIZE, MIN_SIZE},
 };
 
+/// Mount path of [skin_router]
+pub const SKIN_PATH: &str = "/skin";
+
 #[inline(always)]
+/// Routes are grouped by feature, so a new route joins its group instead of reshuffling others
 pub fn skin_router() -> Ohkami {
     Ohkami::new((
         LogRequest,
         openapi::Tag("skin"),
+        SkinFile,
+        tee_router(),
+        atlas_router(),
+        remote_router(),
+        composite_router(),
+        "/seven".By(seven_router()),
+        "/validate".By(validate_router()),
+        catalog_router(),
+    ))
+}
+
+#[inline(always)]
+/// Routes rendering one stored skin as a tee, merged into [skin_router].
+/// Static routes take precedence over the path param.
+fn tee_router() -> Ohkami {
+    Ohkami::new((
         "/".GET(skin_handler),
+        "/:file".GET(file_handler),
+        "/animated".GET(animated_handler),
+        "/card".GET(card_handler),
+    ))
+}
+
+#[inline(always)]
+/// Routes returning the UV atlas of a stored skin or its parts, merged into [skin_router]
+fn atlas_router() -> Ohkami {
+    Ohkami::new(("/raw".GET(raw_handler), "/part".GET(part_handler)))
+}
+
+#[inline(always)]
+/// Routes rendering skins from outside of the store, merged into [skin_router]
+fn remote_router() -> Ohkami {
+    Ohkami::new(("/url".GET(url_handler), "/preview".POST(preview_handler)))
+}
+
+#[inline(always)]
+/// Routes rendering many skins into one response, merged into [skin_router]
+fn composite_router() -> Ohkami {
+    Ohkami::new((
+        "/batch".POST(batch_handler),
+        "/sheet".GET(sheet_handler),
+        "/team".GET(team_handler),
+    ))
+}
+
+#[inline(always)]
+/// Routes of Teeworlds 0.7 skin parts, nested under `/seven`
+fn seven_router() -> Ohkami {
+    Ohkami::new(("/".GET(seven_handler), "/store".GET(seven_lock_handler)))
+}
+
+#[inline(always)]
+/// Routes checking skins against the format, nested under `/validate`
+fn validate_router() -> Ohkami {
+    Ohkami::new((
+        "/".POST(validate_handler),
+        "/store".GET(validate_store_handler),
+    ))
+}
+
+#[inline(always)]
+/// Routes describing stored skins and renders without rendering, merged into [skin_router]
+fn catalog_router() -> Ohkami {
+    Ohkami::new((
         "/store".GET(lock_handler),
+        "/info".GET(info_handler),
         "/cache".GET(cache_handler),
     ))
 }
 
-#[derive(Debug, Clone, Deserialize, Hash, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, Default, Deserialize, Hash, PartialEq, Eq, Serialize)]
 /// Base/Default/Main skin query
 pub struct SkinQuery {
     /// **note**: Replace all whitespaces to `_`
     pub name: String,
-    /// DDNet value
-    pub body: Option<u32>,
-    /// DDNet value
-    pub feet: Option<u32>,
+    /// Body color
+    pub body: Option<Color>,
+    /// Feet color
+    pub feet: Option<Color>,
+    /// Output format, negotiated by `Accept` header if not set
+    pub format: Option<Format>,
+    /// How the tee is drawn
+    #[serde(flatten)]
+    pub tee: TeeOptions,
+    /// Which stored skin is drawn
+    #[serde(flatten)]
+    pub player: PlayerOptions,
+    /// Output image options
+    #[serde(flatten)]
+    pub options: RenderOptions,
+    /// Set by other endpoints to render something else from the same skin
+    #[serde(skip_deserializing, default)]
+    pub kind: Kind,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Hash, PartialEq, Eq, Serialize)]
+/// How the tee is drawn, flattened into every query which renders one
+pub struct TeeOptions {
+    /// Eye expression
+    #[serde(default)]
+    pub eyes: Eyes,
+    /// Longest side of the output image in pixels
+    #[serde(default, deserialize_with = "parsed_option")]
+    pub size: Option<u32>,
+    /// Resampling filter used with `size`
+    #[serde(default)]
+    pub filter: Filter,
+    /// Look direction in degrees, clockwise from the right
+    #[serde(default, deserialize_with = "parsed_option")]
+    pub angle: Option<i32>,
+    /// Look direction, used if `angle` is not set
+    pub look: Option<Look>,
+    /// Mirror the whole tee horizontally
+    #[serde(default, deserialize_with = "parsed")]
+    pub flip: bool,
+}
+
+impl TeeOptions {
+    /// Look direction in degrees
+    pub fn angle(&self) -> Option<i32> {
+        self.angle.or(self.look.map(Look::angle))
+    }
+
+    /// Check values which can't be expressed by types
+    pub fn validate(&self) -> Result<(), Error> {
+        if let Some(size) = self.size
+            && !(MIN_SIZE..=MAX_SIZE).contains(&size)
+        {
+            return Err(Error::InvalidQuery(format!(
+                "size must be in range {MIN_SIZE}..={MAX_SIZE}, got {size}"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Reset values without effect on the render
+    pub fn normalize(&mut self) {
+        if self.size.is_none() {
+            self.filter = Filter::default();
+        }
+        // `look` is a preset of `angle`, and looking right is the default layout
+        self.angle = self
+            .angle()
+            .map(|angle| angle.rem_euclid(360))
+            .filter(|&angle| angle != 0);
+        self.look = None;
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Hash, PartialEq, Eq, Serialize)]
+/// Which stored skin is drawn for a player, flattened into [SkinQuery] and queries of
+/// path-style URLs
+pub struct PlayerOptions {
+    /// Player state, frozen players are drawn with the frozen skin
+    #[serde(default)]
+    pub state: State,
+    /// Stored skin rendered if `name` is unknown, `none` disables `FALLBACK_SKIN`.
+    ///
+    /// Cleared once resolved, so it's not a part of the cache key.
+    pub fallback: Option<String>,
+}
+
+impl SkinQuery {
+    /// Eye expression, frozen players are always in pain
+    pub fn eyes(&self) -> Eyes {
+        match self.player.state {
+            State::Normal => self.tee.eyes,
+            State::Frozen => Eyes::Pain,
+        }
+    }
+
+    /// Check query values which can't be expressed by types, then [normalize](Self::normalize)
+    pub fn validate(&mut self) -> Result<(), Error> {
+        self.tee.validate()?;
+        let padding = self.options.padding;
+        if padding > MAX_PADDING {
+            return Err(Error::InvalidQuery(format!(
+                "padding must be in range 0..={MAX_PADDING}, got {padding}"
+            )));
+        }
+        if let Some(size) = self.tee.size
+            && size < MIN_SIZE + 2 * padding
+        {
+            return Err(Error::InvalidQuery(format!(
+                "padding {padding} leaves less than {MIN_SIZE} pixels of size {size}"
+            )));
+        }
+        self.normalize();
+        Ok(())
+    }
+
+    /// Reset values without effect on the render, so equal renders share a cache key
+    pub fn normalize(&mut self) {
+        self.tee.normalize();
+        if matches!(self.kind, Kind::Part(part) if part != Part::Eye) {
+            self.tee.eyes = Eyes::default();
+        }
+        // Frozen skin is drawn as is with its own eyes
+        if self.player.state == State::Frozen {
+            self.body = None;
+            self.feet = None;
+            self.tee.eyes = Eyes::default();
+        }
+    }
+
+    /// Composed tee is returned as is, apart from encoding
+    pub fn is_plain(&self) -> bool {
+        self.tee.size.is_none() && !self.tee.flip && self.options == RenderOptions::default()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, Hash, PartialEq, Eq, Serialize)]
+/// Output image options around the tee, flattened into queries which render tees
+pub struct RenderOptions {
+    /// Color under the tee
+    #[serde(default)]
+    pub bg: Background,
+    /// Empty space around the tee in output pixels
+    #[serde(default, deserialize_with = "parsed")]
+    pub padding: u32,
+    /// Drop shadow under the tee
+    #[serde(default, deserialize_with = "parsed")]
+    pub shadow: bool,
+    #[serde(default)]
+    pub canvas: Canvas,
+}
+
+impl Schema for RenderOptions {
+    fn schema() -> impl Into<SchemaRef> {
+        openapi::component(
+            "RenderOptions",
+            openapi::object()
+                .optional(
+                    "bg",
+                    openapi::string()
+                        .description(
+                            "Color under the tee: hex, `rgb(...)`, color name or `transparent`",
+                        )
+                        .example("#36393f")
+                        .default("transparent"),
+                )
+                .optional(
+                    "padding",
+                    openapi::integer()
+                        .description("Empty space around the tee in output pixels")
+                        .minimum(0)
+                        .maximum(MAX_PADDING as i32)
+                        .default(0),
+                )
+                .optional(
+                    "shadow",
+                    openapi::bool()
+                        .description("Drop shadow under the tee")
+                        .default(false),
+                )
+                .optional(
+                    "canvas",
+                    openapi::string()
+                        .description("`square` centers the tee on a square")
+                        .enumerates(["tight", "square"])
+                        .default("tight"),
+                ),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, Hash, PartialEq, Eq, Serialize)]
+#[serde(try_from = "String", into = "String")]
+/// Background of the output image
+pub enum Background {
+    #[default]
+    Transparent,
+    Color(Rgb),
+}
+
+impl TryFrom<String> for Background {
+    type Error = Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        if value.trim().eq_ignore_ascii_case("transparent") {
+            Ok(Background::Transparent)
+        } else {
+            Rgb::try_from(value).map(Background::Color)
+        }
+    }
+}
+
+impl From<Background> for String {
+    fn from(value: Background) -> Self {
+        match value {
+            Background::Transparent => "transparent".to_string(),
+            Background::Color(color) => color.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, Hash, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+/// Player state shown by the rendered tee
+pub enum State {
+    #[default]
+    Normal,
+    /// Drawn like DDNet does: frozen skin without custom colors and with pain eyes
+    Frozen,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, Hash, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+/// Shape of the output image
+pub enum Canvas {
+    /// Fits the tee
+    #[default]
+    Tight,
+    /// Tee centered on a square, e.g. for round avatars
+    Square,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Hash, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+/// What is rendered from the skin
+pub enum Kind {
+    /// Composed still tee
+    #[default]
+    Tee,
+    /// Composed walk cycle
+    Animated(Animation),
+    /// Recolored UV atlas
+    Atlas,
+    /// Single recolored part of the UV atlas
+    Part(Part),
+    /// Tee composed from Teeworlds 0.7 parts
+    Seven(Box<Seven>),
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, Hash, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+/// Eye expression of the rendered tee
+pub enum Eyes {
+    Normal,
+    Angry,
+    Pain,
+    #[default]
+    Happy,
+    Surprise,
+    /// Normal eyes squashed vertically, as DDNet draws blinking
+    Blink,
+}
+
+impl Eyes {
+    /// Eye of the skin drawn for the expression, `None` for [Eyes::Blink] which has no eye
+    /// of its own and is drawn by [compose](crate::render::compose)
+    pub const fn eye_type(self) -> Option<EyeType> {
+        match self {
+            Eyes::Normal => Some(EyeType::Normal),
+            Eyes::Angry => Some(EyeType::Angry),
+            Eyes::Pain => Some(EyeType::Pain),
+            Eyes::Happy => Some(EyeType::Happy),
+            Eyes::Surprise => Some(EyeType::Surprise),
+            Eyes::Blink => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Hash, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+/// Look direction of the rendered tee
+pub enum Look {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl Look {
+    /// Angle in degrees, clockwise from the right
+    pub const fn angle(self) -> i32 {
+        match self {
+            Look::Right => 0,
+            Look::Down => 90,
+            Look::Left => 180,
+            Look::Up => 270,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, Hash, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+/// Resampling filter for scaled output
+pub enum Filter {
+    /// Keeps hard pixel edges
+    Nearest,
+    Linear,
+    Cubic,
+    #[default]
+    Lanczos,
+}
+
+impl From<Filter> for FilterType {
+    fn from(value: Filter) -> Self {
+        match value {
+            Filter::Nearest => FilterType::Nearest,
+            Filter::Linear => FilterType::Triangle,
+            Filter::Cubic => FilterType::CatmullRom,
+            Filter::Lanczos => FilterType::Lanczos3,
+        }
+    }
 }
 
 impl Schema for SkinQuery {
     fn schema() -> impl Into<SchemaRef> {
         openapi::component(
             "SkinQuery",
+            flatten::<RenderOptions>(flatten::<PlayerOptions>(flatten::<TeeOptions>(
+                openapi::object()
+                    .property(
+                        "name",
+                        openapi::string()
+                            .format("a-zA-Z0-9_")
+                            .description("Skin name")
+                            .example("zzz"),
+                    )
+                    .optional("body", Color::schema())
+                    .optional("feet", Color::schema())
+                    .optional(
+                        "format",
+                        openapi::string()
+                            .description("Output format, negotiated by `Accept` header if not set")
+                            .enumerates(["png", "webp", "jpeg"])
+                            .nullable(),
+                    ),
+            ))),
+        )
+    }
+}
+
+impl Schema for TeeOptions {
+    fn schema() -> impl Into<SchemaRef> {
+        openapi::component(
+            "TeeOptions",
             openapi::object()
-                .property(
-                    "name",
+                .optional(
+                    "eyes",
+                    openapi::string()
+                        .description("Eye expression")
+                        .enumerates(["normal", "angry", "pain", "happy", "surprise", "blink"])
+                        .default("happy"),
+                )
+                .optional(
+                    "size",
+                    openapi::integer()
+                        .description("Longest side of the output image in pixels")
+                        .minimum(MIN_SIZE as i32)
+                        .maximum(MAX_SIZE as i32)
+                        .example(256)
+                        .nullable(),
+                )
+                .optional(
+                    "filter",
                     openapi::string()
-                        .format("a-zA-Z0-9_")
-                        .description("Skin name")
-                        .example("zzz"),
+                        .description("Resampling filter used with `size`")
+                        .enumerates(["nearest", "linear", "cubic", "lanczos"])
+                        .default("lanczos"),
                 )
                 .optional(
-                    "body",
+                    "angle",
+                    openapi::integer()
+                        .description("Look direction in degrees, clockwise from the right")
+                        .example(45)
+                        .nullable(),
+                )
+                .optional(
+                    "look",
                     openapi::string()
-                        .description("DDNet value")
-                        .example("32132114")
+                        .description("Look direction, used if `angle` is not set")
+                        .enumerates(["left", "right", "up", "down"])
                         .nullable(),
                 )
                 .optional(
-                    "feet",
+                    "flip",
+                    openapi::bool()
+                        .description("Mirror the whole tee horizontally")
+                        .default(false),
+                ),
+        )
+    }
+}
+
+impl Schema for PlayerOptions {
+    fn schema() -> impl Into<SchemaRef> {
+        openapi::component(
+            "PlayerOptions",
+            openapi::object()
+                .optional(
+                    "state",
+                    openapi::string()
+                        .description("`frozen` draws the frozen skin with pain eyes, like DDNet")
+                        .enumerates(["normal", "frozen"])
+                        .default("normal"),
+                )
+                .optional(
+                    "fallback",
                     openapi::string()
-                        .description("DDNet value")
-                        .example("32132114")
+                        .description(
+                            "Stored skin rendered if `name` is unknown, `none` disables the server default",
+                        )
+                        .example("default")
                         .nullable(),
                 ),
         )
@@ -76,22 +570,193 @@ impl Display for SkinQuery {
             .field("name", &self.name)
             .field("body", &self.body)
             .field("feet", &self.feet)
+            .field("format", &self.format)
+            .field("tee", &self.tee)
+            .field("player", &self.player)
+            .field("options", &self.options)
+            .field("kind", &self.kind)
             .finish()
     }
 }
 
 #[inline(always)]
-#[instrument(skip_all, level="info", fields(name=%query.name, body=?query.body, feet=?query.feet))]
+#[instrument(skip_all, level="info", fields(name=%query.name, body=?query.body, feet=?query.feet, eyes=?query.tee.eyes, size=?query.tee.size, format=?query.format))]
 #[operation({
     summary: "Get rendered skin image",
 })]
 /// Represent GET method to return a builded skin by query
+///
+/// Answers `304 Not Modified` if `If-None-Match` or `If-Modified-Since` matches the stored skin.
+/// `HEAD` is answered without rendering.
 async fn skin_handler(
     Context(state): Context<'_, Arc<AppState>>,
-    Query(query): Query<SkinQuery>,
-) -> Result<OK<Png>, Error> {
-    Ok(OK(Png(match state.cache.get(&query).await {
-        Ok(Some(e)) => e.to_vec(),
-        _ => state.lock.get(state.cache.clone(), query).await?,
-    })))
+    FlatQuery(mut query): FlatQuery<SkinQuery>,
+    Head(head): Head,
+    accept: Option<Accept<&str>>,
+    if_none_match: Option<IfNoneMatch<&str>>,
+    if_modified_since: Option<IfModifiedSince<&str>>,
+) -> Result<Cached<Image>, Error> {
+    query.validate()?;
+    let negotiated = query.format.is_none();
+    let format = query
+        .format
+        .or_else(|| accept.and_then(|Accept(accept)| Format::from_accept(accept)))
+        .unwrap_or_default();
+    // Negotiated format is a part of the cache key
+    query.format = Some(format);
+    cached_skin(
+        state,
+        query,
+        negotiated,
+        head,
+        if_none_match.map(|IfNoneMatch(tags)| tags),
+        if_modified_since.map(|IfModifiedSince(since)| since),
+    )
+    .await
+}
+
+/// Render validated [SkinQuery] with its format set, or answer `304 Not Modified` by its validators.
+///
+/// `HEAD` takes a cached render or only checks the stored skin exists, never rendering.
+pub async fn cached_skin(
+    state: &AppState,
+    mut query: SkinQuery,
+    negotiated: bool,
+    head: bool,
+    if_none_match: Option<&str>,
+    if_modified_since: Option<&str>,
+) -> Result<Cached<Image>, Error> {
+    let format = query.format.unwrap_or_default();
+    let (meta, fallback) = state.lock.resolve(&mut query)?;
+    let validators = Validators::new(&meta, &query, state.max_age, negotiated);
+    if validators.is_fresh(if_none_match, if_modified_since) {
+        return Ok(Cached {
+            body: None,
+            validators,
+            fallback,
+        });
+    }
+    let image = match state.cache.get(&query).await {
+        Ok(Some(e)) => Image::new(format, e.to_vec()),
+        _ if head => Image::head(format),
+        _ => Image::new(format, state.lock.get(state.cache.clone(), query).await?),
+    };
+    // `Vary` comes from the validators, which also send it with `304 Not Modified`
+    Ok(Cached {
+        body: Some(image),
+        validators,
+        fallback,
+    })
+}
+
+/// Cached render of [SkinQuery], rendered on a miss unless it's `HEAD`, which only checks
+/// the stored skin exists and gets `None`
+pub async fn cached_render(
+    state: &AppState,
+    query: SkinQuery,
+    head: bool,
+) -> Result<Option<Vec<u8>>, Error> {
+    match state.cache.get(&query).await {
+        Ok(Some(e)) => Ok(Some(e.to_vec())),
+        _ if head => state.lock.meta(&query).map(|_| None),
+        _ => state.lock.get(state.cache.clone(), query).await.map(Some),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tee_morphosis::tee::parts::EyeType;
+
+    use crate::app::{
+        color::Color,
+        part::Part,
+        skin::{Eyes, Filter, Kind, Look, PlayerOptions, SkinQuery, State, TeeOptions},
+    };
+
+    #[test]
+    fn query_normalization() {
+        let mut query = SkinQuery {
+            tee: TeeOptions {
+                filter: Filter::Nearest,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        query.validate().unwrap();
+        assert_eq!(query, SkinQuery::default());
+
+        let mut query = SkinQuery {
+            tee: TeeOptions {
+                size: Some(64),
+                filter: Filter::Nearest,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        query.validate().unwrap();
+        assert_eq!(query.tee.filter, Filter::Nearest);
+
+        for (angle, look, normalized) in [
+            (Some(-90), None, Some(270)),
+            (Some(450), Some(Look::Left), Some(90)),
+            (Some(360), None, None),
+            (None, Some(Look::Up), Some(270)),
+            (None, Some(Look::Right), None),
+        ] {
+            let mut query = SkinQuery {
+                tee: TeeOptions {
+                    angle,
+                    look,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            query.validate().unwrap();
+            assert_eq!((query.tee.angle, query.tee.look), (normalized, None));
+        }
+
+        for (part, eyes) in [(Part::Eye, Eyes::Blink), (Part::Body, Eyes::Happy)] {
+            let mut query = SkinQuery {
+                tee: TeeOptions {
+                    eyes: Eyes::Blink,
+                    ..Default::default()
+                },
+                kind: Kind::Part(part),
+                ..Default::default()
+            };
+            query.normalize();
+            assert_eq!(query.tee.eyes, eyes);
+        }
+
+        let mut query = SkinQuery {
+            body: Some(Color(0)),
+            tee: TeeOptions {
+                eyes: Eyes::Angry,
+                ..Default::default()
+            },
+            player: PlayerOptions {
+                state: State::Frozen,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        query.normalize();
+        assert_eq!(
+            query,
+            SkinQuery {
+                player: PlayerOptions {
+                    state: State::Frozen,
+                    ..Default::default()
+                },
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn eye_types() {
+        assert_eq!(Eyes::Happy.eye_type(), Some(EyeType::Happy));
+        assert_eq!(Eyes::Normal.eye_type(), Some(EyeType::Normal));
+        assert_eq!(Eyes::Blink.eye_type(), None);
+    }
 }
@@ -0,0 +1,25 @@
+use std::borrow::Cow;
+
+use ohkami::{
+    claw::content::IntoContent,
+    openapi::{self, Schema},
+};
+
+/// Represents an SVG image content.
+#[derive(Debug, Schema)]
+#[openapi(component)]
+pub struct Svg(pub String);
+
+impl IntoContent for Svg {
+    const CONTENT_TYPE: &'static str = "image/svg+xml";
+
+    #[inline(always)]
+    fn into_content(self) -> Result<std::borrow::Cow<'static, [u8]>, impl std::fmt::Display> {
+        Result::<_, std::convert::Infallible>::Ok(Cow::Owned(self.0.into_bytes()))
+    }
+
+    #[inline(always)]
+    fn openapi_responsebody() -> impl Into<openapi::schema::SchemaRef> {
+        openapi::string()
+    }
+}
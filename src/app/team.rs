@@ -0,0 +1,275 @@
+use std::{fmt::Display, str::FromStr, sync::Arc};
+
+use futures::future;
+use ohkami::{
+    FromRequest, Request,
+    claw::header::Accept,
+    fang::Context,
+    openapi::{self, Schema, SchemaRef, operation},
+    serde::Deserialize,
+};
+use tokio::task::spawn_blocking;
+use tracing::instrument;
+
+use crate::{
+    app::{
+        AppState,
+        color::Color,
+        conditional::Head,
+        format::{Format, Image},
+        query::{FlatQuery, flatten},
+        skin::{SkinQuery, TeeOptions},
+    },
+    error::Error,
+    render::{self, MAX_SIZE, MIN_SIZE, team},
+};
+
+/// Upper bound for tees of a line-up
+pub const MAX_TEAM: usize = 16;
+/// Upper bound for overlap of neighbour tees in percent
+pub const MAX_OVERLAP: u32 = 90;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// One tee of a line-up, written as `name:body:feet` where colors can be omitted
+pub struct TeamTee {
+    pub name: String,
+    pub body: Option<Color>,
+    pub feet: Option<Color>,
+}
+
+impl FromStr for TeamTee {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(3, ':');
+        let name = parts.next().unwrap_or_default().trim();
+        if name.is_empty() {
+            return Err(Error::InvalidQuery(format!("tee `{s}` has no name")));
+        }
+        let mut color = || {
+            parts
+                .next()
+                .map(str::trim)
+                .filter(|color| !color.is_empty())
+                .map(str::parse::<Color>)
+                .transpose()
+        };
+        Ok(Self {
+            name: name.to_string(),
+            body: color()?,
+            feet: color()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+/// All `tee` query params in order, which can't be collected by [FlatQuery]
+pub struct Tees(pub Vec<TeamTee>);
+
+impl<'req> FromRequest<'req> for Tees {
+    type Error = Error;
+
+    fn from_request(req: &'req Request) -> Option<Result<Self, Self::Error>> {
+        let tees = req
+            .query
+            .iter()
+            .filter(|(key, _)| key == "tee")
+            .map(|(_, value)| value.parse())
+            .collect::<Result<Vec<_>, _>>()
+            .and_then(|tees| match tees.len() {
+                0 => Err(Error::InvalidQuery(
+                    "at least one tee is expected".to_string(),
+                )),
+                len if len > MAX_TEAM => Err(Error::InvalidQuery(format!(
+                    "line-up must have at most {MAX_TEAM} tees, got {len}"
+                ))),
+                _ => Ok(Tees(tees)),
+            });
+        Some(tees)
+    }
+
+    fn openapi_inbound() -> openapi::Inbound {
+        openapi::Inbound::Params(vec![openapi::Parameter::in_query(
+            "tee",
+            openapi::string()
+                .description("Repeated `name:body:feet` of each tee in order, colors are optional")
+                .example("default:red:%23ff8800"),
+        )])
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+/// Team line-up query, options are shared by all tees
+pub struct TeamQuery {
+    /// Output format, negotiated by `Accept` header if not set
+    pub format: Option<Format>,
+    /// How each tee is drawn, `size` is the longest side of each tee
+    #[serde(flatten)]
+    pub tee: TeeOptions,
+    /// Width of a tee covered by the next one in percent
+    #[serde(default)]
+    pub overlap: u32,
+}
+
+impl TeamQuery {
+    /// Check query values which can't be expressed by types
+    pub fn validate(&self) -> Result<(), Error> {
+        self.tee.validate()?;
+        if self.overlap > MAX_OVERLAP {
+            return Err(Error::InvalidQuery(format!(
+                "overlap must be in range 0..={MAX_OVERLAP}, got {}",
+                self.overlap
+            )));
+        }
+        Ok(())
+    }
+
+    /// [SkinQuery] of one tee, always rendered to PNG
+    pub fn skin(
+        &self,
+        tee: TeamTee,
+    ) -> SkinQuery {
+        let mut query = SkinQuery {
+            name: tee.name,
+            body: tee.body,
+            feet: tee.feet,
+            format: Some(Format::Png),
+            tee: self.tee.clone(),
+            ..Default::default()
+        };
+        query.normalize();
+        query
+    }
+}
+
+impl Schema for TeamQuery {
+    fn schema() -> impl Into<SchemaRef> {
+        openapi::component(
+            "TeamQuery",
+            // Own properties go last, so `size` replaces the flattened one
+            flatten::<TeeOptions>(openapi::object())
+                .optional(
+                    "size",
+                    openapi::integer()
+                        .description("Longest side of each tee in pixels")
+                        .minimum(MIN_SIZE as i32)
+                        .maximum(MAX_SIZE as i32)
+                        .example(64)
+                        .nullable(),
+                )
+                .optional(
+                    "format",
+                    openapi::string()
+                        .description("Output format, negotiated by `Accept` header if not set")
+                        .enumerates(["png", "webp", "jpeg"])
+                        .nullable(),
+                )
+                .optional(
+                    "overlap",
+                    openapi::integer()
+                        .description("Width of a tee covered by the next one in percent")
+                        .minimum(0)
+                        .maximum(MAX_OVERLAP as i32)
+                        .default(0),
+                ),
+        )
+    }
+}
+
+impl Display for TeamQuery {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        f.debug_struct("TeamQuery")
+            .field("format", &self.format)
+            .field("tee", &self.tee)
+            .field("overlap", &self.overlap)
+            .finish()
+    }
+}
+
+#[inline(always)]
+#[instrument(skip_all, level="info", fields(len=%tees.len(), size=?query.tee.size, overlap=%query.overlap, format=?query.format))]
+#[operation({
+    summary: "Get several tees side by side",
+})]
+/// Represent GET method to return a line-up of independently colored tees
+pub async fn team_handler(
+    Context(state): Context<'_, Arc<AppState>>,
+    FlatQuery(query): FlatQuery<TeamQuery>,
+    Tees(tees): Tees,
+    accept: Option<Accept<&str>>,
+    Head(head): Head,
+) -> Result<Image, Error> {
+    query.validate()?;
+    let negotiated = query.format.is_none();
+    let format = query
+        .format
+        .or_else(|| accept.and_then(|Accept(accept)| Format::from_accept(accept)))
+        .unwrap_or_default();
+    if head {
+        for tee in tees {
+            state.lock.meta(&query.skin(tee))?;
+        }
+        return Ok(Image {
+            negotiated,
+            ..Image::head(format)
+        });
+    }
+
+    let tees = future::try_join_all(tees.into_iter().map(async |tee| {
+        let skin = query.skin(tee);
+        match state.cache.get(&skin).await {
+            Ok(Some(e)) => Ok(e.to_vec()),
+            _ => state.lock.get(state.cache.clone(), skin).await,
+        }
+    }))
+    .await?;
+
+    let overlap = query.overlap;
+    let data = spawn_blocking(
+        #[inline]
+        move || render::encode(&team::lineup(&tees, overlap)?, format),
+    )
+    .await??;
+    Ok(Image {
+        negotiated,
+        ..Image::new(format, data.to_vec())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn team_tee_parsing() {
+        assert_eq!(
+            "default".parse::<TeamTee>().unwrap(),
+            TeamTee {
+                name: "default".to_string(),
+                body: None,
+                feet: None,
+            }
+        );
+        assert_eq!(
+            "x_ninja::#00f".parse::<TeamTee>().unwrap(),
+            TeamTee {
+                name: "x_ninja".to_string(),
+                body: None,
+                feet: Some("blue".parse().unwrap()),
+            }
+        );
+        assert_eq!(
+            "default:65280:rgb(255, 0, 0)".parse::<TeamTee>().unwrap(),
+            TeamTee {
+                name: "default".to_string(),
+                body: Some(Color(65280)),
+                feet: Some("red".parse().unwrap()),
+            }
+        );
+        assert!(":red".parse::<TeamTee>().is_err());
+        assert!("default:nope".parse::<TeamTee>().is_err());
+    }
+}
@@ -0,0 +1,162 @@
+use std::{fmt::Display, sync::Arc};
+
+use ohkami::{
+    claw::header::Accept,
+    fang::Context,
+    openapi::{self, Schema, SchemaRef, operation},
+    serde::Deserialize,
+};
+use tokio::task::spawn_blocking;
+use tracing::instrument;
+
+use crate::{
+    app::{
+        AppState,
+        color::Color,
+        conditional::Head,
+        format::{Format, Image},
+        query::{FlatQuery, flatten},
+        skin::{RenderOptions, SkinQuery, TeeOptions},
+    },
+    error::Error,
+    render,
+};
+
+#[derive(Debug, Clone, Deserialize)]
+/// Remote skin query
+pub struct UrlQuery {
+    /// URL of PNG skin on one of allowed hosts
+    pub src: String,
+    /// Body color
+    pub body: Option<Color>,
+    /// Feet color
+    pub feet: Option<Color>,
+    /// Output format, negotiated by `Accept` header if not set
+    pub format: Option<Format>,
+    /// How the tee is drawn
+    #[serde(flatten)]
+    pub tee: TeeOptions,
+    /// Output image options
+    #[serde(flatten)]
+    pub options: RenderOptions,
+}
+
+impl From<UrlQuery> for SkinQuery {
+    fn from(value: UrlQuery) -> Self {
+        SkinQuery {
+            name: value.src,
+            body: value.body,
+            feet: value.feet,
+            format: value.format,
+            tee: value.tee,
+            options: value.options,
+            ..Default::default()
+        }
+    }
+}
+
+impl Schema for UrlQuery {
+    fn schema() -> impl Into<SchemaRef> {
+        openapi::component(
+            "UrlQuery",
+            flatten::<RenderOptions>(flatten::<TeeOptions>(
+                openapi::object()
+                    .property(
+                        "src",
+                        openapi::string()
+                            .format("uri")
+                            .description("URL of PNG skin on one of allowed hosts")
+                            .example("https://example.com/skins/clan.png"),
+                    )
+                    .optional("body", Color::schema())
+                    .optional("feet", Color::schema())
+                    .optional(
+                        "format",
+                        openapi::string()
+                            .description("Output format, negotiated by `Accept` header if not set")
+                            .enumerates(["png", "webp", "jpeg"])
+                            .nullable(),
+                    ),
+            )),
+        )
+    }
+}
+
+impl Display for UrlQuery {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        f.debug_struct("UrlQuery")
+            .field("src", &self.src)
+            .field("body", &self.body)
+            .field("feet", &self.feet)
+            .field("format", &self.format)
+            .field("tee", &self.tee)
+            .field("options", &self.options)
+            .finish()
+    }
+}
+
+#[inline(always)]
+#[instrument(skip_all, level="info", fields(src=%query.src, body=?query.body, feet=?query.feet, eyes=?query.tee.eyes))]
+#[operation({
+    summary: "Render skin from allowed URL",
+})]
+/// Represent GET method to render a skin downloaded from one of allowed hosts.
+///
+/// Downloads are reused for a while, renders are cached by URL and SHA-256 of the downloaded
+/// skin, so updated skins are rendered again once the download expires.
+pub async fn url_handler(
+    Context(state): Context<'_, Arc<AppState>>,
+    FlatQuery(query): FlatQuery<UrlQuery>,
+    accept: Option<Accept<&str>>,
+    Head(head): Head,
+) -> Result<Image, Error> {
+    let mut query = SkinQuery::from(query);
+    query.validate()?;
+    let negotiated = query.format.is_none();
+    let format = query
+        .format
+        .or_else(|| accept.and_then(|Accept(accept)| Format::from_accept(accept)))
+        .unwrap_or_default();
+    query.format = Some(format);
+
+    let unrendered = Image {
+        negotiated,
+        ..Image::head(format)
+    };
+    let skin = match head {
+        true => state.remote.peek(&query.name).await?,
+        false => Some(state.remote.fetch(&query.name).await?),
+    };
+    let Some(skin) = skin else {
+        return Ok(unrendered);
+    };
+    query.name = format!("{}#{}", query.name, skin.hash);
+    let data = match state.cache.get(&query).await {
+        Ok(Some(e)) => e.to_vec(),
+        _ if head => return Ok(unrendered),
+        _ => {
+            // Reject oversized downloads from their header, before decoding them
+            render::validate::check_header(&skin.data)?;
+            let key = query.clone();
+            let data = spawn_blocking(
+                #[inline]
+                move || render::skin(skin.data.to_vec(), &query),
+            )
+            .await?
+            .map_err(|e| match e {
+                // Downloaded atlas is not a valid skin, unlike stored ones
+                Error::Tee(e) => Error::InvalidSkin(e.to_string()),
+                e => e,
+            })?;
+            state.cache.save(key, data.clone()).await?;
+            data.to_vec()
+        }
+    };
+    Ok(Image {
+        negotiated,
+        ..Image::new(format, data)
+    })
+}
@@ -0,0 +1,112 @@
+use std::sync::Arc;
+
+use futures::{StreamExt, TryStreamExt, future, stream};
+use ohkami::{
+    Json, Query,
+    fang::Context,
+    openapi::{self, Schema, SchemaRef, operation},
+    serde::Deserialize,
+};
+use serde::Serialize;
+use tokio::task::spawn_blocking;
+use tracing::instrument;
+
+pub use crate::render::validate::{Issue, Severity, SkinReport};
+use crate::{
+    app::{AppState, png::Png},
+    error::Error,
+    render,
+};
+
+/// Stored skins read and validated at once
+pub const MAX_CONCURRENT: usize = 16;
+
+#[derive(Debug, Serialize, Schema)]
+#[openapi(component)]
+/// [SkinReport] of a stored skin
+pub struct StoredReport {
+    pub name: String,
+    pub report: SkinReport,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+/// Stored skins validation query
+pub struct StoredQuery {
+    /// Include skins without issues
+    #[serde(default)]
+    pub all: bool,
+}
+
+impl Schema for StoredQuery {
+    fn schema() -> impl Into<SchemaRef> {
+        openapi::component(
+            "StoredQuery",
+            openapi::object().optional(
+                "all",
+                openapi::bool()
+                    .description("Include skins without issues")
+                    .default(false),
+            ),
+        )
+    }
+}
+
+#[inline(always)]
+#[instrument(skip_all, level="info", fields(len=%data.len()))]
+#[operation({
+    summary: "Check uploaded skin for problems",
+})]
+/// Represent POST method to return a validation report of an uploaded skin.
+///
+/// Body size is bounded by `OHKAMI_REQUEST_PAYLOAD_LIMIT`.
+pub async fn validate_handler(Png(data): Png) -> Result<Json<SkinReport>, Error> {
+    let report = spawn_blocking(
+        #[inline]
+        move || render::validate::validate(&data),
+    )
+    .await?;
+    Ok(Json(report))
+}
+
+#[inline(always)]
+#[instrument(skip_all, level="info", fields(all=%query.all))]
+#[operation({
+    summary: "Check all stored skins for problems",
+})]
+/// Represent GET method to return validation reports of stored skins, sorted by name.
+///
+/// Skins are validated a few at a time, ones which can't be read are reported as invalid.
+pub async fn validate_store_handler(
+    Context(state): Context<'_, Arc<AppState>>,
+    Query(query): Query<StoredQuery>,
+) -> Result<Json<Vec<StoredReport>>, Error> {
+    let names = state
+        .lock
+        .store
+        .iter()
+        .map(|x| x.key().clone())
+        .collect::<Vec<_>>();
+    let mut reports = stream::iter(names)
+        .map(async |name| {
+            let report = match state.lock.uv(&name).await {
+                Ok(data) => {
+                    spawn_blocking(
+                        #[inline]
+                        move || render::validate::validate(&data),
+                    )
+                    .await?
+                }
+                Err(e) => SkinReport::unreadable(e),
+            };
+            Ok::<_, Error>(StoredReport {
+                name,
+                report,
+            })
+        })
+        .buffer_unordered(MAX_CONCURRENT)
+        .try_filter(|stored| future::ready(query.all || !stored.report.issues.is_empty()))
+        .try_collect::<Vec<_>>()
+        .await?;
+    reports.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(Json(reports))
+}
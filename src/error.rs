@@ -8,13 +8,16 @@ use ohkami::{
 use reqwest::header::ToStrError;
 use thiserror::Error;
 use tokio::{io, task::JoinError};
-use tracing::{error, instrument};
+use tracing::instrument;
 
-#[derive(Serialize, Schema)]
+#[derive(Debug, Serialize, Schema)]
 #[openapi(component)]
-struct ErrorResponse {
-    code: u16,
-    message: String,
+pub struct ErrorResponse {
+    pub code: u16,
+    pub message: String,
+    /// Stored skin names close to an unknown one
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub suggestions: Vec<String>,
 }
 
 #[derive(Debug, Error)]
@@ -27,8 +30,21 @@ pub enum Error {
     TaskJoin(#[from] JoinError),
     #[error("Tee error")]
     Tee(#[from] tee_morphosis::error::TeeError),
-    #[error("Query expected name, but got none")]
-    QueryNameNotFound,
+    #[error("Skin not found: {name}")]
+    SkinNotFound {
+        name: String,
+        suggestions: Vec<String>,
+    },
+    #[error("Frozen skin not found: {0}")]
+    FrozenSkinNotFound(String),
+    #[error("Invalid query: {0}")]
+    InvalidQuery(String),
+    #[error("Image error")]
+    Image(#[from] image::ImageError),
+    #[error("Invalid skin: {0}")]
+    InvalidSkin(String),
+    #[error("Zip error")]
+    Zip(#[from] zip::result::ZipError),
     #[error("Reqwest error")]
     Reqwest(#[from] reqwest::Error),
     #[error("Reqwest header convertation error")]
@@ -41,57 +57,104 @@ pub enum Error {
     },
     #[error("Fail to download: {name}, {error}")]
     DownloadFailed { name: String, error: String },
+    #[error("Upstream error: {src}, {error}")]
+    Upstream { src: String, error: String },
 }
 
-impl IntoResponse for Error {
+impl Error {
+    /// Body shown to the client, details of internal errors are only logged
     #[instrument]
-    fn into_response(self) -> Response {
+    pub fn error_response(self) -> ErrorResponse {
         match self {
-            Error::QueryNameNotFound => Response::BadRequest().with_json(ErrorResponse {
+            Error::SkinNotFound {
+                name,
+                suggestions,
+            } => ErrorResponse {
+                code: 404,
+                message: format!("Skin `{name}` not found"),
+                suggestions,
+            },
+            Error::InvalidQuery(message) => ErrorResponse {
+                code: 400,
+                message,
+                suggestions: Vec::new(),
+            },
+            Error::InvalidSkin(message) => ErrorResponse {
                 code: 400,
-                message: "Query expected name, but got none".to_string(),
-            }),
+                message: format!("Invalid skin: {message}"),
+                suggestions: Vec::new(),
+            },
+            Error::FrozenSkinNotFound(name) => {
+                tracing::error!("Frozen skin `{}` is not stored", name);
+                ErrorResponse {
+                    code: 500,
+                    message: "Frozen skin is not available".to_string(),
+                    suggestions: Vec::new(),
+                }
+            }
             Error::Io(e) => {
                 tracing::error!("I/O error: {}", e);
-                Response::InternalServerError().with_json(ErrorResponse {
+                ErrorResponse {
                     code: 500,
                     message: "Skin not found".to_string(),
-                })
+                    suggestions: Vec::new(),
+                }
             }
             Error::Tee(e) => {
                 tracing::error!("Tee error: {}", e);
-                Response::InternalServerError().with_json(ErrorResponse {
+                ErrorResponse {
                     code: 500,
                     message: "Failed to render UV".to_string(),
-                })
+                    suggestions: Vec::new(),
+                }
+            }
+            Error::Image(e) => {
+                tracing::error!("Image error: {}", e);
+                ErrorResponse {
+                    code: 500,
+                    message: "Failed to process image".to_string(),
+                    suggestions: Vec::new(),
+                }
+            }
+            Error::Zip(e) => {
+                tracing::error!("Zip error: {}", e);
+                ErrorResponse {
+                    code: 500,
+                    message: "Failed to create archive".to_string(),
+                    suggestions: Vec::new(),
+                }
             }
             Error::Reqwest(e) => {
                 tracing::error!("Reqwest error: {}", e);
-                Response::InternalServerError().with_json(ErrorResponse {
+                ErrorResponse {
                     code: 500,
                     message: "External request failed".to_string(),
-                })
+                    suggestions: Vec::new(),
+                }
             }
             Error::ToStrError(e) => {
                 tracing::error!("Header conversion error: {}", e);
-                Response::InternalServerError().with_json(ErrorResponse {
+                ErrorResponse {
                     code: 500,
                     message: "Invalid header value".to_string(),
-                })
+                    suggestions: Vec::new(),
+                }
             }
             Error::TaskJoin(e) => {
                 tracing::error!("Task join error: {}", e);
-                Response::InternalServerError().with_json(ErrorResponse {
+                ErrorResponse {
                     code: 500,
                     message: "Background task failed".to_string(),
-                })
+                    suggestions: Vec::new(),
+                }
             }
             Error::Json(e) => {
                 tracing::error!("JSON error: {}", e);
-                Response::InternalServerError().with_json(ErrorResponse {
+                ErrorResponse {
                     code: 500,
                     message: "JSON processing failed".to_string(),
-                })
+                    suggestions: Vec::new(),
+                }
             }
             Error::SaveFailed {
                 path,
@@ -99,23 +162,46 @@ impl IntoResponse for Error {
                 error,
             } => {
                 tracing::error!("Save failed: {} at {:?}: {}", name, path, error);
-                Response::InternalServerError().with_json(ErrorResponse {
+                ErrorResponse {
                     code: 500,
                     message: format!("Failed to save {}", name),
-                })
+                    suggestions: Vec::new(),
+                }
             }
             Error::DownloadFailed {
                 name,
                 error,
             } => {
                 tracing::error!("Download failed: {}: {}", name, error);
-                Response::InternalServerError().with_json(ErrorResponse {
+                ErrorResponse {
                     code: 500,
                     message: format!("Failed to download {}", name),
-                })
+                    suggestions: Vec::new(),
+                }
+            }
+            Error::Upstream { src, error } => {
+                tracing::warn!("Upstream failed: {}: {}", src, error);
+                ErrorResponse {
+                    code: 502,
+                    message: format!("Failed to download {}", src),
+                    suggestions: Vec::new(),
+                }
             }
         }
     }
+}
+
+impl IntoResponse for Error {
+    fn into_response(self) -> Response {
+        let body = self.error_response();
+        match body.code {
+            400 => Response::BadRequest(),
+            404 => Response::NotFound(),
+            502 => Response::BadGateway(),
+            _ => Response::InternalServerError(),
+        }
+        .with_json(body)
+    }
 
     fn openapi_responses() -> openapi::Responses {
         openapi::Responses::new([
@@ -124,11 +210,21 @@ impl IntoResponse for Error {
                 openapi::Response::when("Bad request - invalid query parameters")
                     .content("application/json", <ErrorResponse as Schema>::schema()),
             ),
+            (
+                404,
+                openapi::Response::when("Skin not found - closest stored names in `suggestions`")
+                    .content("application/json", <ErrorResponse as Schema>::schema()),
+            ),
             (
                 500,
                 openapi::Response::when("Internal server error")
                     .content("application/json", <ErrorResponse as Schema>::schema()),
             ),
+            (
+                502,
+                openapi::Response::when("Bad gateway - remote skin host failed")
+                    .content("application/json", <ErrorResponse as Schema>::schema()),
+            ),
         ])
     }
 }
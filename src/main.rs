@@ -1,17 +1,23 @@
-use std::{sync::Arc, time::Duration};
+use std::{path::Path, sync::Arc, time::Duration};
 
 use tokio::{fs, time::sleep};
 use tracing::{Level, info};
 
 use crate::{
-    app::app,
+    app::{app, conditional::DEFAULT_MAX_AGE},
     cache::CacheStore,
-    rsync::{lock::LockStore, try_sync_skins},
+    remote::RemoteStore,
+    rsync::{
+        lock::{DEFAULT_FALLBACK_SKIN, LockStore},
+        try_sync_seven, try_sync_skins,
+    },
 };
 
 pub mod app;
 pub mod cache;
 pub mod error;
+pub mod remote;
+pub mod render;
 pub mod rsync;
 
 fn init_logger(level: Level) {
@@ -40,12 +46,34 @@ async fn main() {
         "0.0.0.0:{}",
         std::env::var("PORT").expect("PORT must be set")
     );
-    let lock = Arc::new(
-        LockStore::read(std::env::var("STORE_PATH").expect("STORE_PATH must be set"))
+    let store_path = std::env::var("STORE_PATH").expect("STORE_PATH must be set");
+    let mut lock = LockStore::read(&store_path).await.unwrap();
+    if let Ok(frozen_skin) = std::env::var("FROZEN_SKIN")
+        && !frozen_skin.is_empty()
+    {
+        lock.frozen_skin = frozen_skin;
+    }
+    lock.fallback_skin = match std::env::var("FALLBACK_SKIN") {
+        Ok(fallback_skin) if fallback_skin.is_empty() || fallback_skin == "none" => None,
+        Ok(fallback_skin) => Some(fallback_skin),
+        Err(_) => Some(DEFAULT_FALLBACK_SKIN.to_string()),
+    };
+    let lock = Arc::new(lock);
+    // Teeworlds 0.7 skin parts have their own lock namespace
+    let seven = Arc::new(
+        LockStore::read(Path::new(&store_path).join("seven"))
             .await
             .unwrap(),
     );
+    let seven_source = std::env::var("SEVEN_SKINS_URL")
+        .ok()
+        .filter(|url| !url.is_empty());
     let cache = Arc::new(CacheStore::new().await);
+    let remote = Arc::new(RemoteStore::from_env());
+    let max_age = std::env::var("SKIN_MAX_AGE")
+        .ok()
+        .and_then(|max_age| max_age.parse().ok())
+        .unwrap_or(DEFAULT_MAX_AGE);
 
     fs::create_dir("static").await.ok();
 
@@ -65,8 +93,28 @@ async fn main() {
         });
     }
 
+    '_SYNC_SEVEN: {
+        let Some(source) = seven_source else {
+            info!("SEVEN_SKINS_URL is not set, 0.7 skins are not synced");
+            break '_SYNC_SEVEN;
+        };
+        let seven = seven.clone();
+        tokio::spawn(async move {
+            loop {
+                if let Err(err) = try_sync_seven(seven.clone(), &source).await {
+                    tracing::error!("Ошибка при выполнении try_sync_seven: {err:?}");
+                } else {
+                    tracing::info!("Скины 0.7 успешно синхронизированы");
+                }
+
+                sleep(Duration::from_secs(60 * 60)).await;
+            }
+        });
+    }
+
     '_CACHE: {
         let cache = cache.clone();
+        let remote = remote.clone();
         tokio::spawn(async move {
             loop {
                 sleep(Duration::from_secs(60 * 60)).await;
@@ -80,10 +128,11 @@ async fn main() {
                     info!(name=%key.name, body=?key.body, feet=?key.feet, "Item has removed from cache");
                     cache.store.remove(&key);
                 }
+                remote.clear_expired();
                 tracing::info!("Cache cleared");
             }
         });
     }
 
-    app(lock, cache, &address).await
+    app(lock, seven, cache, remote, max_age, &address).await
 }
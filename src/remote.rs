@@ -0,0 +1,265 @@
+use std::{
+    env,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use bytes::Bytes;
+use dashmap::DashMap;
+use reqwest::{Client, Response, StatusCode, Url, redirect};
+use sha2::{Digest, Sha256};
+use tracing::info;
+
+use crate::error::Error;
+
+/// Default upper bound of a downloaded skin in bytes
+pub const DEFAULT_MAX_SIZE: usize = 64 * 1024;
+/// Default time limit of a skin download in seconds
+pub const DEFAULT_TIMEOUT: u64 = 5;
+/// Default time a downloaded skin is reused without asking its host again, in seconds
+pub const DEFAULT_TTL: u64 = 5 * 60;
+/// Redirects followed inside allowed hosts
+const MAX_REDIRECTS: usize = 3;
+
+/// Downloads skins from hosts outside of the store.
+///
+/// Configured by `SKIN_URL_HOSTS` (comma separated allowlist), `SKIN_URL_MAX_SIZE` (bytes),
+/// `SKIN_URL_TIMEOUT` and `SKIN_URL_TTL` (seconds). Nothing is allowed if `SKIN_URL_HOSTS`
+/// is not set.
+#[derive(Debug)]
+pub struct RemoteStore {
+    pub hosts: Arc<Vec<String>>,
+    pub max_size: usize,
+    pub client: Client,
+    /// Downloaded skins by URL
+    pub skins: DashMap<String, RemoteSkin>,
+    /// Time a downloaded skin is reused
+    pub ttl: Duration,
+}
+
+pub type Remote = Arc<RemoteStore>;
+
+/// Downloaded skin
+#[derive(Debug, Clone)]
+pub struct RemoteSkin {
+    pub data: Bytes,
+    /// Hex SHA-256 of `data`, to tell apart versions of the skin under the same URL
+    pub hash: String,
+    pub fetched: Instant,
+}
+
+impl RemoteStore {
+    pub fn new(
+        hosts: Vec<String>,
+        max_size: usize,
+        timeout: Duration,
+        ttl: Duration,
+    ) -> Self {
+        let hosts = Arc::new(hosts);
+        let allowed = hosts.clone();
+        let client = Client::builder()
+            .timeout(timeout)
+            .redirect(redirect::Policy::custom(move |attempt| {
+                if attempt.previous().len() >= MAX_REDIRECTS {
+                    attempt.error("too many redirects")
+                } else if is_allowed(&allowed, attempt.url()) {
+                    attempt.follow()
+                } else {
+                    attempt.stop()
+                }
+            }))
+            .build()
+            .expect("TLS backend must be available");
+        Self {
+            hosts,
+            max_size,
+            client,
+            skins: DashMap::new(),
+            ttl,
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let hosts = env::var("SKIN_URL_HOSTS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|host| host.trim().to_ascii_lowercase())
+            .filter(|host| !host.is_empty())
+            .collect::<Vec<_>>();
+        let max_size = env::var("SKIN_URL_MAX_SIZE")
+            .ok()
+            .and_then(|size| size.parse().ok())
+            .unwrap_or(DEFAULT_MAX_SIZE);
+        let timeout = env::var("SKIN_URL_TIMEOUT")
+            .ok()
+            .and_then(|timeout| timeout.parse().ok())
+            .unwrap_or(DEFAULT_TIMEOUT);
+        let ttl = env::var("SKIN_URL_TTL")
+            .ok()
+            .and_then(|ttl| ttl.parse().ok())
+            .unwrap_or(DEFAULT_TTL);
+        info!(?hosts, max_size, timeout, ttl, "Remote skins configured");
+        Self::new(
+            hosts,
+            max_size,
+            Duration::from_secs(timeout),
+            Duration::from_secs(ttl),
+        )
+    }
+
+    /// Skin from allowed `src`, downloaded again once it's older than `ttl`
+    pub async fn fetch(
+        &self,
+        src: &str,
+    ) -> Result<RemoteSkin, Error> {
+        let url = self.url(src)?;
+        if let Some(skin) = self.fresh(src) {
+            return Ok(skin);
+        }
+
+        let skin = self.download(src, url).await?;
+        self.skins.insert(src.to_string(), skin.clone());
+        Ok(skin)
+    }
+
+    /// Check allowed `src` without downloading it: its fresh download if there is one,
+    /// otherwise `None` once its host answers `HEAD` like it would a download
+    pub async fn peek(
+        &self,
+        src: &str,
+    ) -> Result<Option<RemoteSkin>, Error> {
+        let url = self.url(src)?;
+        if let Some(skin) = self.fresh(src) {
+            return Ok(Some(skin));
+        }
+
+        let resp = self
+            .client
+            .head(url)
+            .send()
+            .await
+            .map_err(|e| upstream(src, e))?;
+        self.check(src, resp)?;
+        Ok(None)
+    }
+
+    /// Parsed `src`, if its host is allowed
+    fn url(
+        &self,
+        src: &str,
+    ) -> Result<Url, Error> {
+        let url = Url::parse(src)
+            .map_err(|e| Error::InvalidQuery(format!("invalid src `{src}`: {e}")))?;
+        if !is_allowed(&self.hosts, &url) {
+            return Err(Error::InvalidQuery(format!(
+                "host of `{src}` is not allowed"
+            )));
+        }
+        Ok(url)
+    }
+
+    /// Download of `src` younger than `ttl`
+    fn fresh(
+        &self,
+        src: &str,
+    ) -> Option<RemoteSkin> {
+        self.skins
+            .get(src)
+            .filter(|skin| skin.fetched.elapsed() < self.ttl)
+            .map(|skin| skin.clone())
+    }
+
+    /// Map the status and announced size of the host's response to errors
+    fn check(
+        &self,
+        src: &str,
+        resp: Response,
+    ) -> Result<Response, Error> {
+        if matches!(resp.status(), StatusCode::NOT_FOUND | StatusCode::GONE) {
+            return Err(Error::SkinNotFound {
+                name: src.to_string(),
+                suggestions: Vec::new(),
+            });
+        }
+        let resp = resp.error_for_status().map_err(|e| upstream(src, e))?;
+        if resp.status().is_redirection() {
+            return Err(Error::InvalidQuery(format!(
+                "`{src}` redirects outside of allowed hosts"
+            )));
+        }
+        if resp
+            .content_length()
+            .is_some_and(|len| len > self.max_size as u64)
+        {
+            return Err(self.too_large(src));
+        }
+        Ok(resp)
+    }
+
+    fn too_large(
+        &self,
+        src: &str,
+    ) -> Error {
+        Error::InvalidSkin(format!("`{src}` is larger than {} bytes", self.max_size))
+    }
+
+    /// Download a skin, keeping under the size limit
+    async fn download(
+        &self,
+        src: &str,
+        url: Url,
+    ) -> Result<RemoteSkin, Error> {
+        let resp = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| upstream(src, e))?;
+        let mut resp = self.check(src, resp)?;
+
+        let mut data = Vec::new();
+        while let Some(chunk) = resp.chunk().await.map_err(|e| upstream(src, e))? {
+            if data.len() + chunk.len() > self.max_size {
+                return Err(self.too_large(src));
+            }
+            data.extend_from_slice(&chunk);
+        }
+
+        Ok(RemoteSkin {
+            hash: Sha256::digest(&data)
+                .iter()
+                .map(|b| format!("{b:02x}"))
+                .collect(),
+            data: data.into(),
+            fetched: Instant::now(),
+        })
+    }
+
+    /// Forget skins older than `ttl`
+    pub fn clear_expired(&self) {
+        self.skins
+            .retain(|_, skin| skin.fetched.elapsed() < self.ttl);
+    }
+}
+
+/// Failed request to the host of `src`
+fn upstream(
+    src: &str,
+    error: reqwest::Error,
+) -> Error {
+    Error::Upstream {
+        src: src.to_string(),
+        error: error.to_string(),
+    }
+}
+
+/// `url` is HTTP(S) and its host is one of `hosts`
+fn is_allowed(
+    hosts: &[String],
+    url: &Url,
+) -> bool {
+    matches!(url.scheme(), "http" | "https")
+        && url
+            .host_str()
+            .is_some_and(|host| hosts.iter().any(|allowed| allowed == host))
+}
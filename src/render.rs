@@ -0,0 +1,385 @@
+pub mod animation;
+pub mod card;
+pub mod info;
+pub mod seven;
+pub mod sheet;
+pub mod team;
+pub mod validate;
+
+use std::io::Cursor;
+
+use bytes::Bytes;
+use image::{
+    ExtendedColorType, ImageEncoder, ImageFormat, Rgb, RgbImage, Rgba, RgbaImage,
+    codecs::{jpeg::JpegEncoder, webp::WebPEncoder},
+    imageops::FilterType,
+};
+use tee_morphosis::tee::{
+    Tee,
+    parts::{EyeType, EyeTypeData, TeePart},
+    skin::{Skin, TEE_SKIN_LAYOUT},
+    uv::UVPart,
+};
+
+use crate::{
+    app::{
+        color,
+        format::Format,
+        part::Part,
+        skin::{Background, Canvas, Eyes, Kind, SkinQuery, State},
+    },
+    error::Error,
+};
+
+/// Version of the renderer, bump it when the same query renders differently, so `ETag`s change
+pub const RENDER_VERSION: u32 = 1;
+/// Upper bound for the longest side of a rendered tee
+pub const MAX_SIZE: u32 = 1024;
+/// Lower bound for the longest side of a rendered tee
+pub const MIN_SIZE: u32 = 8;
+/// Upper bound for padding around a rendered tee
+pub const MAX_PADDING: u32 = 256;
+/// Quality of JPEG output
+pub const JPEG_QUALITY: u8 = 90;
+/// Color under transparent pixels of formats without alpha
+pub const JPEG_BACKGROUND: Rgb<u8> = Rgb([255, 255, 255]);
+/// Height of blinking eyes relative to open ones, taken from DDNet `RenderTee`
+const BLINK_SCALE: f32 = 0.15 / 0.4;
+/// DDNet `BaseSize` of a tee, which matches body size of [TEE_SKIN_LAYOUT]
+const BASE_SIZE: f32 = 64.;
+/// Opacity of a drop shadow
+const SHADOW_ALPHA: f32 = 0.5;
+
+/// Load [Tee] from PNG UV atlas, then recolor and render it by [SkinQuery]
+pub fn skin(
+    uv: Vec<u8>,
+    query: &SkinQuery,
+) -> Result<Bytes, Error> {
+    let mut tee = Tee::new(uv.into(), ImageFormat::Png)?;
+    recolor(&mut tee, query);
+    render(&tee, query)
+}
+
+/// Compose [Tee] and apply output options of [SkinQuery]
+pub fn render(
+    tee: &Tee,
+    query: &SkinQuery,
+) -> Result<Bytes, Error> {
+    match &query.kind {
+        Kind::Tee => (),
+        Kind::Animated(animation) => return animation::render(tee, query, *animation),
+        Kind::Atlas => return encode(&atlas(tee), Format::Png),
+        Kind::Part(part) => return encode(&crop(tee, *part, query.tee.eyes), Format::Png),
+        Kind::Seven(_) => {
+            return Err(Error::InvalidQuery(
+                "0.7 skins are composed from parts, not from a single skin".to_string(),
+            ));
+        }
+    }
+
+    let png = compose(tee, layout(query.tee.angle()), query.eyes())?;
+    let format = query.format.unwrap_or_default();
+    if query.is_plain() && format == Format::Png {
+        return Ok(png);
+    }
+
+    encode(&transform(decode(&png)?, query), format)
+}
+
+/// Apply colors of [SkinQuery] to [Tee] parts, frozen tees keep original colors
+pub fn recolor(
+    tee: &mut Tee,
+    query: &SkinQuery,
+) {
+    if query.player.state == State::Frozen {
+        return;
+    }
+    if let Some(color) = query.body {
+        tee.apply_hsl_to_parts(color.hsl(), &[TeePart::Body, TeePart::BodyShadow]);
+    }
+    if let Some(color) = query.feet {
+        tee.apply_hsl_to_parts(color.hsl(), &[TeePart::Feet, TeePart::FeetShadow]);
+    }
+}
+
+/// Put [Tee] parts back to their places of the UV atlas
+pub fn atlas(tee: &Tee) -> RgbaImage {
+    let uv = tee.used_uv;
+    let mut img = RgbaImage::new(uv.container.0, uv.container.1);
+    let mut put = |part: &RgbaImage, at: UVPart| {
+        image::imageops::replace(&mut img, part, at.x as i64, at.y as i64)
+    };
+    put(&tee.body.value, uv.body);
+    put(&tee.body.shadow, uv.body_shadow);
+    put(&tee.feet.value, uv.feet);
+    put(&tee.feet.shadow, uv.feet_shadow);
+    put(&tee.hand.value, uv.hand);
+    put(&tee.hand.shadow, uv.hand_shadow);
+    for (eye, at) in tee.eye.iter().zip(uv.eyes) {
+        match eye {
+            EyeTypeData::Normal(eye)
+            | EyeTypeData::Angry(eye)
+            | EyeTypeData::Pain(eye)
+            | EyeTypeData::Happy(eye)
+            | EyeTypeData::Empty(eye)
+            | EyeTypeData::Surprise(eye) => put(eye, at),
+        }
+    }
+    img
+}
+
+/// Take a single [Part] of [Tee]
+pub fn crop(
+    tee: &Tee,
+    part: Part,
+    eyes: Eyes,
+) -> RgbaImage {
+    match part {
+        Part::Body => tee.body.value.clone(),
+        Part::BodyShadow => tee.body.shadow.clone(),
+        Part::Feet => tee.feet.value.clone(),
+        Part::FeetShadow => tee.feet.shadow.clone(),
+        Part::Hand => tee.hand.value.clone(),
+        Part::HandShadow => tee.hand.shadow.clone(),
+        Part::Eye => match eyes.eye_type() {
+            Some(eye) => tee.get_eye(eye).clone(),
+            None => closed_eye(tee),
+        },
+    }
+}
+
+/// Apply options of [SkinQuery] to composed tee.
+///
+/// Padding is added after scaling, so the longest side of the output is still `size`.
+pub fn transform(
+    mut img: RgbaImage,
+    query: &SkinQuery,
+) -> RgbaImage {
+    let options = query.options;
+    if query.tee.flip {
+        image::imageops::flip_horizontal_in_place(&mut img);
+    }
+    if options.shadow {
+        img = shadow(&img);
+    }
+    if let Some(size) = query.tee.size {
+        img = resize(&img, size - 2 * options.padding, query.tee.filter.into());
+    }
+    let (w, h) = img.dimensions();
+    let (w, h) = match options.canvas {
+        Canvas::Tight => (w, h),
+        Canvas::Square => (w.max(h), w.max(h)),
+    };
+    if (w, h) != img.dimensions() || options.padding > 0 {
+        img = center(&img, w + 2 * options.padding, h + 2 * options.padding);
+    }
+    if let Background::Color(color::Rgb([r, g, b])) = options.bg {
+        let mut filled = RgbaImage::from_pixel(img.width(), img.height(), Rgba([r, g, b, 255]));
+        image::imageops::overlay(&mut filled, &img, 0, 0);
+        img = filled;
+    }
+    img
+}
+
+/// Put image in the middle of a transparent `w`x`h` canvas
+fn center(
+    img: &RgbaImage,
+    w: u32,
+    h: u32,
+) -> RgbaImage {
+    let mut canvas = RgbaImage::new(w, h);
+    image::imageops::overlay(
+        &mut canvas,
+        img,
+        ((w - img.width()) / 2) as i64,
+        ((h - img.height()) / 2) as i64,
+    );
+    canvas
+}
+
+/// Draw blurred silhouette of the tee under it, growing the canvas to fit the blur
+fn shadow(img: &RgbaImage) -> RgbaImage {
+    let (w, h) = img.dimensions();
+    let sigma = h as f32 / 24.;
+    let offset = h / 16;
+    let margin = (sigma * 3.).ceil() as u32 + offset;
+    let silhouette = RgbaImage::from_fn(w, h, |x, y| {
+        Rgba([
+            0,
+            0,
+            0,
+            (img.get_pixel(x, y)[3] as f32 * SHADOW_ALPHA) as u8,
+        ])
+    });
+    let mut shadow = RgbaImage::new(w + 2 * margin, h + 2 * margin);
+    image::imageops::overlay(
+        &mut shadow,
+        &silhouette,
+        margin as i64,
+        (margin + offset) as i64,
+    );
+    let mut shadow = image::imageops::blur(&shadow, sigma);
+    image::imageops::overlay(&mut shadow, img, margin as i64, margin as i64);
+    shadow
+}
+
+/// [TEE_SKIN_LAYOUT] with eyes looking at `angle` degrees, clockwise from the right.
+///
+/// Offsets follow DDNet `RenderTee`, relative to the default layout which looks right.
+pub fn layout(angle: Option<i32>) -> Skin {
+    let Some(angle) = angle else {
+        return TEE_SKIN_LAYOUT;
+    };
+    let (dy, dx) = (angle as f32).to_radians().sin_cos();
+    let x = (dx - 1.) * 0.125 * BASE_SIZE;
+    let y = dy * 0.10 * BASE_SIZE;
+    // Eyes get closer when the tee looks sideways
+    let separation = -0.010 * (dx.abs() - 1.) * BASE_SIZE;
+    let shift = |((ex, ey), scale): ((i64, i64), f32), x: f32| {
+        ((ex + x.round() as i64, ey + y.round() as i64), scale)
+    };
+
+    Skin {
+        first_eyes: shift(TEE_SKIN_LAYOUT.first_eyes, x - separation),
+        second_eyes: shift(TEE_SKIN_LAYOUT.second_eyes, x + separation),
+        ..TEE_SKIN_LAYOUT
+    }
+}
+
+/// Compose [Tee] as PNG with [Eyes], including ones missing in the skin
+pub fn compose(
+    tee: &Tee,
+    skin: Skin,
+    eyes: Eyes,
+) -> Result<Bytes, Error> {
+    if let Some(eye) = eyes.eye_type() {
+        return Ok(tee.compose_png(skin, eye)?);
+    }
+
+    let mut tee = tee.clone();
+    tee.eye[EyeType::Normal.index()] = EyeTypeData::Normal(closed_eye(&tee));
+    Ok(tee.compose_png(skin, EyeType::Normal)?)
+}
+
+/// Normal eye of [Tee] squashed vertically, as DDNet draws blinking
+pub fn closed_eye(tee: &Tee) -> RgbaImage {
+    let normal = tee.get_eye(EyeType::Normal);
+    let (w, h) = normal.dimensions();
+    let squashed = image::imageops::resize(
+        normal,
+        w,
+        ((h as f32 * BLINK_SCALE) as u32).max(1),
+        FilterType::Triangle,
+    );
+    let mut closed = RgbaImage::new(w, h);
+    image::imageops::overlay(
+        &mut closed,
+        &squashed,
+        0,
+        ((h - squashed.height()) / 2) as i64,
+    );
+    closed
+}
+
+/// Decode composed tee back to pixels
+pub fn decode(data: &[u8]) -> Result<RgbaImage, Error> {
+    Ok(image::load_from_memory_with_format(data, ImageFormat::Png)?.into_rgba8())
+}
+
+/// Encode pixels to [Format]
+pub fn encode(
+    img: &RgbaImage,
+    format: Format,
+) -> Result<Bytes, Error> {
+    let mut buf = Vec::new();
+    match format {
+        Format::Png => img.write_to(&mut Cursor::new(&mut buf), ImageFormat::Png)?,
+        Format::Webp => WebPEncoder::new_lossless(&mut buf).write_image(
+            img.as_raw(),
+            img.width(),
+            img.height(),
+            ExtendedColorType::Rgba8,
+        )?,
+        Format::Jpeg => JpegEncoder::new_with_quality(&mut buf, JPEG_QUALITY).write_image(
+            flatten(img, JPEG_BACKGROUND).as_raw(),
+            img.width(),
+            img.height(),
+            ExtendedColorType::Rgb8,
+        )?,
+    }
+    Ok(Bytes::from(buf))
+}
+
+/// Scale image so its longest side is equal to `size`, keeping aspect ratio
+pub fn resize(
+    img: &RgbaImage,
+    size: u32,
+    filter: FilterType,
+) -> RgbaImage {
+    let (w, h) = img.dimensions();
+    let (w, h) = if w >= h { (size, (h * size / w).max(1)) } else { ((w * size / h).max(1), size) };
+    image::imageops::resize(img, w, h, filter)
+}
+
+/// Blend image over solid `background`, dropping alpha channel
+pub fn flatten(
+    img: &RgbaImage,
+    background: Rgb<u8>,
+) -> RgbImage {
+    RgbImage::from_fn(img.width(), img.height(), |x, y| {
+        let [r, g, b, a] = img.get_pixel(x, y).0;
+        let blend =
+            |fg: u8, bg: u8| ((fg as u32 * a as u32 + bg as u32 * (255 - a as u32)) / 255) as u8;
+        Rgb([
+            blend(r, background[0]),
+            blend(g, background[1]),
+            blend(b, background[2]),
+        ])
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use image::RgbaImage;
+
+    use crate::{
+        app::{
+            color::Rgb,
+            skin::{Background, Canvas, RenderOptions, SkinQuery, TeeOptions},
+        },
+        render::transform,
+    };
+
+    #[test]
+    fn transform_canvas() {
+        let tee = RgbaImage::from_pixel(96, 64, image::Rgba([255, 0, 0, 255]));
+        let query = SkinQuery {
+            tee: TeeOptions {
+                size: Some(128),
+                ..Default::default()
+            },
+            options: RenderOptions {
+                padding: 16,
+                canvas: Canvas::Square,
+                bg: Background::Color(Rgb([0, 0, 255])),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let img = transform(tee.clone(), &query);
+        assert_eq!(img.dimensions(), (128, 128));
+        assert_eq!(img.get_pixel(0, 0).0, [0, 0, 255, 255]);
+        assert_eq!(img.get_pixel(64, 64).0, [255, 0, 0, 255]);
+
+        let query = SkinQuery {
+            options: RenderOptions {
+                padding: 4,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let img = transform(tee, &query);
+        assert_eq!(img.dimensions(), (104, 72));
+        assert_eq!(img.get_pixel(0, 0).0, [0, 0, 0, 0]);
+    }
+}
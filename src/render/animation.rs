@@ -0,0 +1,114 @@
+use std::{f32::consts::TAU, io};
+
+use bytes::Bytes;
+use image::{
+    Delay, Frame, RgbaImage,
+    codecs::gif::{GifEncoder, Repeat},
+};
+use tee_morphosis::tee::{Tee, skin::Skin};
+
+use crate::{
+    app::{
+        animated::{Animation, AnimationFormat},
+        skin::{Eyes, SkinQuery},
+    },
+    error::Error,
+    render,
+};
+
+/// Frames in one loop of the animation
+pub const FRAMES: usize = 12;
+/// How far feet step from their idle position, in pixels of the layout
+const STEP: f32 = 4.;
+/// How high a foot lifts during the step
+const LIFT: f32 = 3.;
+/// How low the body sinks between steps
+const BOB: f32 = 1.5;
+/// Speed of GIF color quantization, `1` is the slowest and `30` is the fastest
+const GIF_SPEED: i32 = 10;
+
+/// Compose all frames of the walk cycle and encode them as [AnimationFormat]
+pub fn render(
+    tee: &Tee,
+    query: &SkinQuery,
+    animation: Animation,
+) -> Result<Bytes, Error> {
+    let base = render::layout(query.tee.angle());
+    let frames = (0..FRAMES)
+        .map(|frame| {
+            // Eyes are closed for the last frame of each loop
+            let eyes = if frame == FRAMES - 1 { Eyes::Blink } else { query.tee.eyes };
+            let png = render::compose(tee, layout(base, frame), eyes)?;
+            Ok(render::transform(render::decode(&png)?, query))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    match animation.format {
+        AnimationFormat::Apng => encode_apng(&frames, animation.delay),
+        AnimationFormat::Gif => encode_gif(frames, animation.delay),
+    }
+}
+
+/// Move body, eyes and feet of `base` layout to the position of `frame`
+fn layout(
+    base: Skin,
+    frame: usize,
+) -> Skin {
+    let phase = frame as f32 / FRAMES as f32 * TAU;
+    let step = (phase.sin() * STEP).round() as i64;
+    let lift = |phase: f32| (-phase.sin().max(0.) * LIFT).round() as i64;
+    let bob = ((phase * 2.).sin().abs() * BOB).round() as i64;
+    let shift = |((x, y), scale): ((i64, i64), f32), dx: i64, dy: i64| ((x + dx, y + dy), scale);
+
+    Skin {
+        body: shift(base.body, 0, bob),
+        first_eyes: shift(base.first_eyes, 0, bob),
+        second_eyes: shift(base.second_eyes, 0, bob),
+        feet: shift(base.feet, step, lift(phase)),
+        feet_back: shift(base.feet_back, -step, lift(phase + TAU / 2.)),
+        ..base
+    }
+}
+
+fn encode_apng(
+    frames: &[RgbaImage],
+    delay: u16,
+) -> Result<Bytes, Error> {
+    let (w, h) = frames[0].dimensions();
+    let mut buf = Vec::new();
+    let mut encoder = png::Encoder::new(&mut buf, w, h);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .set_animated(frames.len() as u32, 0)
+        .map_err(io::Error::from)?;
+    encoder
+        .set_frame_delay(delay, 1000)
+        .map_err(io::Error::from)?;
+    encoder
+        .set_dispose_op(png::DisposeOp::Background)
+        .map_err(io::Error::from)?;
+    let mut writer = encoder.write_header().map_err(io::Error::from)?;
+    for frame in frames {
+        writer
+            .write_image_data(frame.as_raw())
+            .map_err(io::Error::from)?;
+    }
+    writer.finish().map_err(io::Error::from)?;
+    Ok(Bytes::from(buf))
+}
+
+fn encode_gif(
+    frames: Vec<RgbaImage>,
+    delay: u16,
+) -> Result<Bytes, Error> {
+    let mut buf = Vec::new();
+    {
+        let mut encoder = GifEncoder::new_with_speed(&mut buf, GIF_SPEED);
+        encoder.set_repeat(Repeat::Infinite)?;
+        encoder.encode_frames(frames.into_iter().map(|frame| {
+            Frame::from_parts(frame, 0, 0, Delay::from_numer_denom_ms(delay as u32, 1))
+        }))?;
+    }
+    Ok(Bytes::from(buf))
+}
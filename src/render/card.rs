@@ -0,0 +1,91 @@
+use std::io::Cursor;
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use image::ImageReader;
+
+use crate::{app::card::CardQuery, error::Error};
+
+/// Longest side of the tee on a card
+pub const TEE_SIZE: u32 = 96;
+pub const WIDTH: u32 = 320;
+pub const HEIGHT: u32 = 96;
+/// Left edge of labels, right of the tee
+const LABEL_X: u32 = TEE_SIZE + 20;
+const FONT: &str = "Verdana, DejaVu Sans, sans-serif";
+
+/// Build an SVG card embedding rendered PNG `tee` with labels of [CardQuery]
+pub fn svg(
+    tee: &[u8],
+    query: &CardQuery,
+) -> Result<String, Error> {
+    let (width, height) = ImageReader::new(Cursor::new(tee))
+        .with_guessed_format()?
+        .into_dimensions()?;
+    let background = query.background.unwrap_or(query.theme.background());
+    let text = query.text.unwrap_or(query.theme.text());
+    let player = escape(query.player.as_deref().unwrap_or(&query.name));
+
+    let mut labels = match query.clan.as_deref().filter(|clan| !clan.is_empty()) {
+        Some(clan) => format!(
+            r#"<text x="{LABEL_X}" y="44" font-size="20" font-weight="bold">{player}</text><text x="{LABEL_X}" y="70" font-size="14" fill-opacity="0.7">{}</text>"#,
+            escape(clan)
+        ),
+        None => format!(
+            r#"<text x="{LABEL_X}" y="55" font-size="20" font-weight="bold">{player}</text>"#
+        ),
+    };
+    if let Some(country) = &query.country {
+        labels.push_str(&format!(
+            r#"<text x="{}" y="28" font-size="18" text-anchor="end">{}</text>"#,
+            WIDTH - 12,
+            flag(country)
+        ));
+    }
+
+    Ok(format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{HEIGHT}" viewBox="0 0 {WIDTH} {HEIGHT}"><title>{player}</title><rect width="{WIDTH}" height="{HEIGHT}" rx="12" fill="{background}"/><image x="{}" y="{}" width="{width}" height="{height}" href="data:image/png;base64,{}"/><g font-family="{FONT}" fill="{text}">{labels}</g></svg>"#,
+        (LABEL_X - 8).saturating_sub(width) / 2,
+        HEIGHT.saturating_sub(height) / 2,
+        STANDARD.encode(tee),
+    ))
+}
+
+/// Regional indicator symbols of alpha-2 `country`, shown as a flag by emoji fonts
+fn flag(country: &str) -> String {
+    country
+        .to_ascii_uppercase()
+        .bytes()
+        .filter_map(|b| char::from_u32(0x1F1E6 + (b - b'A') as u32))
+        .collect()
+}
+
+/// Escape text for SVG content and attributes
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            ch => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn card_labels() {
+        assert_eq!(
+            escape(r#"<a href="x">'&'</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;&apos;&amp;&apos;&lt;/a&gt;"
+        );
+        assert_eq!(flag("de"), "🇩🇪");
+        assert_eq!(flag("UA"), "🇺🇦");
+    }
+}
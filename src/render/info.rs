@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+
+use image::RgbaImage;
+use tee_morphosis::tee::uv::{TEE_UV_LAYOUT, UVPart};
+
+/// Low bits of each channel dropped when bucketing similar colors
+const QUANTIZE_SHIFT: u8 = 3;
+/// Pixels with lower alpha are treated as background
+const MIN_ALPHA: u8 = 128;
+
+/// Dominant colors of body and feet of a UV atlas, `None` for empty parts or sizes which
+/// aren't a multiple of 256x128
+pub fn colors(img: &RgbaImage) -> (Option<[u8; 3]>, Option<[u8; 3]>) {
+    let (w, h) = img.dimensions();
+    let (uv_w, uv_h) = TEE_UV_LAYOUT.container;
+    if w == 0 || w % uv_w != 0 || w / uv_w * uv_h != h {
+        return (None, None);
+    }
+    let scale = w / uv_w;
+    (
+        dominant(img, TEE_UV_LAYOUT.body, scale),
+        dominant(img, TEE_UV_LAYOUT.feet, scale),
+    )
+}
+
+/// Average color of the most common bucket of visible pixels of `part` scaled by `scale`
+pub fn dominant(
+    img: &RgbaImage,
+    part: UVPart,
+    scale: u32,
+) -> Option<[u8; 3]> {
+    // Bucket to pixel count and channel sums
+    let mut buckets = HashMap::<[u8; 3], (u32, [u32; 3])>::new();
+    for y in part.y * scale..(part.y + part.h) * scale {
+        for x in part.x * scale..(part.x + part.w) * scale {
+            let [r, g, b, a] = img.get_pixel(x, y).0;
+            if a < MIN_ALPHA {
+                continue;
+            }
+            let (count, sum) = buckets
+                .entry([r, g, b].map(|channel| channel >> QUANTIZE_SHIFT))
+                .or_default();
+            *count += 1;
+            for (sum, channel) in sum.iter_mut().zip([r, g, b]) {
+                *sum += channel as u32;
+            }
+        }
+    }
+    // Ties go to the darkest bucket, so results don't depend on hash order
+    buckets
+        .into_iter()
+        .max_by(|(a_key, (a, _)), (b_key, (b, _))| a.cmp(b).then(b_key.cmp(a_key)))
+        .map(|(_, (count, sum))| sum.map(|sum| (sum / count) as u8))
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{Rgba, RgbaImage};
+    use tee_morphosis::tee::uv::TEE_UV_LAYOUT;
+
+    use crate::render::info::{colors, dominant};
+
+    #[test]
+    fn dominant_colors() {
+        let mut img = RgbaImage::new(512, 256);
+        let body = TEE_UV_LAYOUT.body;
+        for y in body.y * 2..(body.y + body.h) * 2 {
+            for x in body.x * 2..(body.x + body.w) * 2 {
+                let color = match x % 4 {
+                    0 => [200, 10, 10, 255],
+                    _ => [20, 200, 40, 255],
+                };
+                img.put_pixel(x, y, Rgba(color));
+            }
+        }
+        assert_eq!(dominant(&img, body, 2), Some([20, 200, 40]));
+        assert_eq!(dominant(&img, TEE_UV_LAYOUT.feet, 2), None);
+        assert_eq!(colors(&img), (Some([20, 200, 40]), None));
+
+        let feet = TEE_UV_LAYOUT.feet;
+        for y in feet.y * 2..(feet.y + feet.h) * 2 {
+            for x in feet.x * 2..(feet.x + feet.w) * 2 {
+                img.put_pixel(x, y, Rgba([1, 2, 3, 255]));
+            }
+        }
+        assert_eq!(colors(&img), (Some([20, 200, 40]), Some([1, 2, 3])));
+        assert_eq!(colors(&RgbaImage::new(100, 50)), (None, None));
+    }
+}
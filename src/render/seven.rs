@@ -0,0 +1,290 @@
+use bytes::Bytes;
+use image::{RgbaImage, imageops::FilterType};
+
+use crate::{
+    app::{
+        seven::Seven,
+        skin::{Eyes, SkinQuery},
+    },
+    error::Error,
+    render::{BASE_SIZE, decode, encode, transform},
+};
+
+/// Width of a composed tee, same as for 0.6 skins
+const WIDTH: u32 = 96;
+/// Height of a composed tee, same as for 0.6 skins
+const HEIGHT: u32 = 64;
+/// Tee position on the canvas
+const ORIGIN: (f32, f32) = (48., 36.);
+/// Body offset of the idle animation
+const BODY: (f32, f32) = (0., -4.);
+/// Back foot offset of the idle animation
+const BACK_FOOT: (f32, f32) = (-7., 10.);
+/// Front foot offset of the idle animation
+const FRONT_FOOT: (f32, f32) = (7., 10.);
+/// Offsets of both hands, at the sides of the body
+const HANDS: [(f32, f32); 2] = [(-25., 6.), (25., 6.)];
+/// Teeworlds 0.7 `DARKEST_COLOR_LGT`
+const DARKEST_LGT: f32 = 61. / 255.;
+
+/// Stored PNG images of Teeworlds 0.7 skin parts
+#[derive(Debug)]
+pub struct Parts {
+    pub body: Vec<u8>,
+    pub marking: Option<Vec<u8>>,
+    pub decoration: Option<Vec<u8>>,
+    pub hands: Vec<u8>,
+    pub feet: Vec<u8>,
+    pub eyes: Vec<u8>,
+}
+
+/// Compose [Parts] and apply output options of [SkinQuery]
+pub fn render(
+    parts: &Parts,
+    seven: &Seven,
+    query: &SkinQuery,
+) -> Result<Bytes, Error> {
+    let img = compose(parts, seven, query.tee.angle(), query.tee.eyes)?;
+    encode(&transform(img, query), query.format.unwrap_or_default())
+}
+
+/// Draw [Parts] in the order of Teeworlds 0.7 `RenderTee`: all outlines first, then fillings.
+///
+/// Hands are drawn last, like `RenderHand` does for a tee holding a weapon.
+pub fn compose(
+    parts: &Parts,
+    seven: &Seven,
+    angle: Option<i32>,
+    emote: Eyes,
+) -> Result<RgbaImage, Error> {
+    let body = decode(&parts.body)?;
+    let hands = decode(&parts.hands)?;
+    let feet = decode(&parts.feet)?;
+    let eyes = decode(&parts.eyes)?;
+    let marking = parts.marking.as_deref().map(decode).transpose()?;
+    let decoration = parts.decoration.as_deref().map(decode).transpose()?;
+
+    let body_color = seven.body_color.map(|value| color(value, false));
+    let feet_color = seven.feet_color.map(|value| color(value, false));
+    let size = (BASE_SIZE, BASE_SIZE);
+    let foot_size = (BASE_SIZE, BASE_SIZE / 2.);
+    let foot = tint(&sprite(&feet, (2, 1), (0, 0)), feet_color);
+    let foot_outline = sprite(&feet, (2, 1), (1, 0));
+
+    let mut canvas = RgbaImage::new(WIDTH, HEIGHT);
+    let mut draw = |img: &RgbaImage, position: (f32, f32), size: (f32, f32)| {
+        draw_scaled(&mut canvas, img, position, size)
+    };
+
+    draw(&foot_outline, BACK_FOOT, foot_size);
+    if let Some(decoration) = &decoration {
+        draw(&sprite(decoration, (2, 1), (1, 0)), BODY, size);
+    }
+    draw(&sprite(&body, (2, 2), (0, 0)), BODY, size);
+    draw(&foot_outline, FRONT_FOOT, foot_size);
+
+    draw(&foot, BACK_FOOT, foot_size);
+    if let Some(decoration) = &decoration {
+        let color = seven.decoration_color.map(|value| color(value, false));
+        draw(
+            &tint(&sprite(decoration, (2, 1), (0, 0)), color),
+            BODY,
+            size,
+        );
+    }
+    draw(
+        &tint(&sprite(&body, (2, 2), (1, 0)), body_color),
+        BODY,
+        size,
+    );
+    if let Some(marking) = &marking {
+        let color = seven.marking_color.map(|value| color(value, true));
+        draw(&tint(marking, color), BODY, size);
+    }
+    draw(&sprite(&body, (2, 2), (0, 1)), BODY, size);
+    draw(&sprite(&body, (2, 2), (1, 1)), BODY, size);
+
+    let (dy, dx) = (angle.unwrap_or_default() as f32).to_radians().sin_cos();
+    let cell = match emote {
+        Eyes::Normal | Eyes::Blink => (0, 0),
+        Eyes::Angry => (1, 0),
+        Eyes::Pain => (0, 1),
+        Eyes::Happy => (1, 1),
+        Eyes::Surprise => (0, 2),
+    };
+    let mut eye = tint(
+        &sprite(&eyes, (2, 4), cell),
+        seven.eyes_color.map(|value| color(value, false)),
+    );
+    if dx < 0. {
+        image::imageops::flip_horizontal_in_place(&mut eye);
+    }
+    let eye_scale = BASE_SIZE * 0.60;
+    let eye_height = match emote {
+        Eyes::Blink => BASE_SIZE * 0.15 / 2.,
+        _ => eye_scale / 2.,
+    };
+    let offset = (dx * 0.125 * BASE_SIZE, (-0.05 + dy * 0.10) * BASE_SIZE);
+    draw(
+        &eye,
+        (BODY.0 + offset.0, BODY.1 + offset.1),
+        (eye_scale, eye_height),
+    );
+
+    draw(&foot, FRONT_FOOT, foot_size);
+
+    // 0.7 `RenderHand` quad is 20 units for a 64 units tee
+    let hand_size = (BASE_SIZE * 20. / 64., BASE_SIZE * 20. / 64.);
+    let hand = tint(
+        &sprite(&hands, (2, 1), (0, 0)),
+        seven.hands_color.map(|value| color(value, false)),
+    );
+    let hand_outline = sprite(&hands, (2, 1), (1, 0));
+    for (i, position) in HANDS.into_iter().enumerate() {
+        let (mut hand, mut outline) = (hand.clone(), hand_outline.clone());
+        if i == 0 {
+            image::imageops::flip_horizontal_in_place(&mut hand);
+            image::imageops::flip_horizontal_in_place(&mut outline);
+        }
+        draw(&outline, position, hand_size);
+        draw(&hand, position, hand_size);
+    }
+
+    Ok(canvas)
+}
+
+/// Teeworlds 0.7 color value to RGBA, alpha is taken from the highest byte if `alpha` is set
+pub fn color(
+    value: u32,
+    alpha: bool,
+) -> [f32; 4] {
+    let h = ((value >> 16) & 0xFF) as f32 / 255.;
+    let s = ((value >> 8) & 0xFF) as f32 / 255.;
+    let l = DARKEST_LGT + (value & 0xFF) as f32 / 255. * (1. - DARKEST_LGT);
+    let a = if alpha { ((value >> 24) & 0xFF) as f32 / 255. } else { 1. };
+
+    let c = (1. - (2. * l - 1.).abs()) * s;
+    let h = h * 6.;
+    let x = c * (1. - (h % 2. - 1.).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (c, x, 0.),
+        1 => (x, c, 0.),
+        2 => (0., c, x),
+        3 => (0., x, c),
+        4 => (x, 0., c),
+        _ => (c, 0., x),
+    };
+    let m = l - c / 2.;
+    [r + m, g + m, b + m, a]
+}
+
+/// Cell of a part image split into `grid` of equal cells
+fn sprite(
+    img: &RgbaImage,
+    (columns, rows): (u32, u32),
+    (x, y): (u32, u32),
+) -> RgbaImage {
+    let (w, h) = (img.width() / columns, img.height() / rows);
+    image::imageops::crop_imm(img, x * w, y * h, w, h).to_image()
+}
+
+/// Grayscale part multiplied by `color`, as Teeworlds 0.7 does for custom colors
+fn tint(
+    img: &RgbaImage,
+    color: Option<[f32; 4]>,
+) -> RgbaImage {
+    let Some([r, g, b, a]) = color else {
+        return img.clone();
+    };
+    let mut img = img.clone();
+    for pixel in img.pixels_mut() {
+        let [pr, pg, pb, pa] = pixel.0;
+        let value = (pr as u32 + pg as u32 + pb as u32) as f32 / 3.;
+        pixel.0 = [
+            (value * r) as u8,
+            (value * g) as u8,
+            (value * b) as u8,
+            (pa as f32 * a) as u8,
+        ];
+    }
+    img
+}
+
+/// Draw `img` scaled to `size` with its center at `position` relative to the tee
+fn draw_scaled(
+    canvas: &mut RgbaImage,
+    img: &RgbaImage,
+    (x, y): (f32, f32),
+    (w, h): (f32, f32),
+) {
+    let scaled = image::imageops::resize(
+        img,
+        (w.round() as u32).max(1),
+        (h.round() as u32).max(1),
+        FilterType::Triangle,
+    );
+    image::imageops::overlay(
+        canvas,
+        &scaled,
+        (ORIGIN.0 + x - w / 2.).round() as i64,
+        (ORIGIN.1 + y - h / 2.).round() as i64,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{Rgba, RgbaImage};
+
+    use crate::{
+        app::{format::Format, seven::Seven, skin::Eyes},
+        render::{
+            encode,
+            seven::{HANDS, ORIGIN, Parts, color, compose},
+        },
+    };
+
+    #[test]
+    fn seven_colors() {
+        let [r, g, b, a] = color(0, true);
+        assert_eq!((r, g, b, a), (61. / 255., 61. / 255., 61. / 255., 0.));
+
+        // Saturated red with half alpha
+        let [r, g, b, a] = color(0x80_00_FF_80, true);
+        assert!(r > 0.99 && g < r && (g - b).abs() < f32::EPSILON);
+        assert!((a - 128. / 255.).abs() < f32::EPSILON);
+
+        assert_eq!(color(0x80_00_FF_80, false)[3], 1.);
+    }
+
+    #[test]
+    fn seven_hands() {
+        let png = |img: &RgbaImage| encode(img, Format::Png).unwrap().to_vec();
+        let blank = png(&RgbaImage::new(64, 64));
+        // White hand on the left, black outline on the right
+        let hands = png(&RgbaImage::from_fn(128, 64, |x, _| match x < 64 {
+            true => Rgba([255, 255, 255, 255]),
+            false => Rgba([0, 0, 0, 255]),
+        }));
+        let parts = Parts {
+            body: blank.clone(),
+            marking: None,
+            decoration: None,
+            hands,
+            feet: blank.clone(),
+            eyes: blank,
+        };
+        let hand = |hands_color: Option<u32>| {
+            let seven = Seven {
+                hands_color,
+                ..Default::default()
+            };
+            let img = compose(&parts, &seven, None, Eyes::default()).unwrap();
+            let (x, y) = (ORIGIN.0 + HANDS[1].0, ORIGIN.1 + HANDS[1].1);
+            *img.get_pixel(x as u32, y as u32)
+        };
+
+        assert_eq!(hand(None), Rgba([255, 255, 255, 255]));
+        assert_ne!(hand(Some(0x00_FF_80)), hand(None));
+        assert_ne!(hand(Some(0x00_FF_80)), hand(Some(0x80_FF_80)));
+    }
+}
@@ -0,0 +1,86 @@
+use font8x8::{BASIC_FONTS, UnicodeFonts};
+use image::{Rgba, RgbaImage};
+
+use crate::{error::Error, render::decode};
+
+/// Height of a caption row, fits 8x8 glyphs with padding
+pub const CAPTION_HEIGHT: u32 = 12;
+/// Width and height of a glyph
+const GLYPH: u32 = 8;
+const TEXT: Rgba<u8> = Rgba([255, 255, 255, 255]);
+/// Keeps white captions readable on light backgrounds
+const TEXT_SHADOW: Rgba<u8> = Rgba([0, 0, 0, 255]);
+
+/// Height of a tee in a cell `cell` pixels wide, composed tees are 3:2
+pub const fn tee_height(cell: u32) -> u32 {
+    cell * 2 / 3
+}
+
+/// Put rendered PNG tees into a grid of `columns` cells, `cell` pixels wide.
+///
+/// Tees which failed to render leave an empty cell, so captions still show what is missing.
+pub fn sheet(
+    tees: &[(String, Option<Vec<u8>>)],
+    columns: u32,
+    cell: u32,
+    captions: bool,
+) -> Result<RgbaImage, Error> {
+    let len = tees.len() as u32;
+    let tee_height = tee_height(cell);
+    let row_height = tee_height + if captions { CAPTION_HEIGHT } else { 0 };
+    let mut img = RgbaImage::new(
+        columns.min(len).max(1) * cell,
+        len.div_ceil(columns).max(1) * row_height,
+    );
+
+    for (index, (name, tee)) in (0..).zip(tees) {
+        let (x, y) = ((index % columns) * cell, (index / columns) * row_height);
+        if let Some(tee) = tee {
+            let tee = decode(tee)?;
+            image::imageops::overlay(
+                &mut img,
+                &tee,
+                (x + cell.saturating_sub(tee.width()) / 2) as i64,
+                (y + tee_height.saturating_sub(tee.height()) / 2) as i64,
+            );
+        }
+        if captions {
+            caption(&mut img, name, x, y + tee_height + 2, cell);
+        }
+    }
+    Ok(img)
+}
+
+/// Draw `text` centered in `width` pixels, cut with `.` if it doesn't fit
+fn caption(
+    img: &mut RgbaImage,
+    text: &str,
+    x: u32,
+    y: u32,
+    width: u32,
+) {
+    let max = (width / GLYPH) as usize;
+    let mut text = text.chars().collect::<Vec<_>>();
+    if text.len() > max {
+        text.truncate(max.saturating_sub(1));
+        text.push('.');
+    }
+    let start = x + (width - text.len() as u32 * GLYPH) / 2;
+
+    for (shift, color) in [(1, TEXT_SHADOW), (0, TEXT)] {
+        for (column, ch) in (0..).zip(&text) {
+            let glyph = BASIC_FONTS
+                .get(*ch)
+                .or_else(|| BASIC_FONTS.get('?'))
+                .unwrap_or_default();
+            for (row, bits) in (0..).zip(glyph) {
+                for bit in 0..GLYPH {
+                    let (px, py) = (start + column * GLYPH + bit + shift, y + row + shift);
+                    if bits >> bit & 1 == 1 && px < img.width() && py < img.height() {
+                        img.put_pixel(px, py, color);
+                    }
+                }
+            }
+        }
+    }
+}
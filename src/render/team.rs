@@ -0,0 +1,39 @@
+use image::RgbaImage;
+
+use crate::{error::Error, render::decode};
+
+/// Put rendered PNG tees side by side, each one covering `overlap` percent of the previous one.
+///
+/// Tees are drawn from right to left, so the leftmost tee is on top like in the scoreboard.
+pub fn lineup(
+    tees: &[Vec<u8>],
+    overlap: u32,
+) -> Result<RgbaImage, Error> {
+    let tees = tees
+        .iter()
+        .map(|tee| decode(tee))
+        .collect::<Result<Vec<_>, _>>()?;
+    let steps = tees
+        .iter()
+        .map(|tee| tee.width() - tee.width() * overlap / 100)
+        .collect::<Vec<_>>();
+    let width =
+        steps.iter().sum::<u32>() + tees.last().map_or(0, |tee| tee.width() * overlap / 100);
+    let height = tees.iter().map(RgbaImage::height).max().unwrap_or(0);
+    let mut img = RgbaImage::new(width.max(1), height.max(1));
+
+    let offsets = steps.iter().scan(0, |x, step| {
+        let offset = *x;
+        *x += step;
+        Some(offset)
+    });
+    for (tee, x) in tees.iter().zip(offsets.collect::<Vec<_>>()).rev() {
+        image::imageops::overlay(
+            &mut img,
+            tee,
+            x as i64,
+            ((height - tee.height()) / 2) as i64,
+        );
+    }
+    Ok(img)
+}
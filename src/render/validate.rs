@@ -0,0 +1,318 @@
+use std::io::Cursor;
+
+use image::{ImageDecoder, ImageFormat, RgbaImage, codecs::png::PngDecoder};
+use ohkami::openapi::{self, Schema, SchemaRef};
+use serde::Serialize;
+use tee_morphosis::tee::{
+    Tee,
+    uv::{TEE_UV_LAYOUT, UVPart},
+};
+
+use crate::error::Error;
+
+/// Width and height of the UV atlas the renderer accepts
+const UV_SIZE: (u32, u32) = TEE_UV_LAYOUT.container;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+/// How bad an [Issue] is
+pub enum Severity {
+    /// Skin is broken in game or can't be rendered
+    Error,
+    /// Skin works, but probably not as intended
+    Warning,
+}
+
+impl Schema for Severity {
+    fn schema() -> impl Into<SchemaRef> {
+        openapi::string().enumerates(["error", "warning"])
+    }
+}
+
+#[derive(Debug, Serialize, Schema)]
+#[openapi(component)]
+/// Problem found in a skin
+pub struct Issue {
+    pub severity: Severity,
+    /// Part of the UV atlas, if the issue is about one
+    pub part: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+/// Result of skin validation
+pub struct SkinReport {
+    /// Skin has no issues with [Severity::Error]
+    pub valid: bool,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    /// Size is a multiple of 256x128, like DDNet HD skins
+    pub multiple: bool,
+    /// Skin can be rendered by this service
+    pub renderable: bool,
+    pub issues: Vec<Issue>,
+}
+
+impl Schema for SkinReport {
+    fn schema() -> impl Into<SchemaRef> {
+        openapi::component(
+            "SkinReport",
+            openapi::object()
+                .property(
+                    "valid",
+                    openapi::bool().description("Skin has no issues with `error` severity"),
+                )
+                .property("width", openapi::integer().nullable())
+                .property("height", openapi::integer().nullable())
+                .property(
+                    "multiple",
+                    openapi::bool()
+                        .description("Size is a multiple of 256x128, like DDNet HD skins"),
+                )
+                .property(
+                    "renderable",
+                    openapi::bool().description("Skin can be rendered by this service"),
+                )
+                .property("issues", openapi::array(Issue::schema())),
+        )
+    }
+}
+
+impl SkinReport {
+    /// Report of a skin whose file can't be read
+    pub fn unreadable(error: Error) -> Self {
+        let error = match error {
+            Error::Io(e) => e.to_string(),
+            e => e.to_string(),
+        };
+        let mut report = Self::default();
+        report.issue(Severity::Error, None, format!("can't read file: {error}"));
+        report
+    }
+
+    pub fn issue(
+        &mut self,
+        severity: Severity,
+        part: Option<&str>,
+        message: impl Into<String>,
+    ) {
+        self.issues.push(Issue {
+            severity,
+            part: part.map(str::to_string),
+            message: message.into(),
+        });
+    }
+}
+
+/// Pixels of a part by alpha
+#[derive(Debug, Default)]
+struct Alpha {
+    transparent: u32,
+    translucent: u32,
+    opaque: u32,
+}
+
+/// Check uploaded or stored skin for problems DDNet players would notice
+pub fn validate(data: &[u8]) -> SkinReport {
+    let mut report = SkinReport::default();
+    let img = match image::load_from_memory(data) {
+        Ok(img) => img,
+        Err(e) => {
+            report.issue(Severity::Error, None, format!("can't decode image: {e}"));
+            return report;
+        }
+    };
+    if image::guess_format(data).ok() != Some(ImageFormat::Png) {
+        report.issue(Severity::Error, None, "DDNet loads PNG skins only");
+    }
+    let img = img.into_rgba8();
+    let (w, h) = img.dimensions();
+    report.width = Some(w);
+    report.height = Some(h);
+    report.multiple = is_multiple(w, h);
+
+    if report.multiple {
+        lint_parts(&mut report, &img, w / UV_SIZE.0);
+    } else {
+        report.issue(Severity::Error, None, not_multiple(w, h));
+    }
+
+    if (w, h) == UV_SIZE {
+        match Tee::new(data.to_vec().into(), ImageFormat::Png) {
+            Ok(_) => report.renderable = true,
+            Err(e) => report.issue(Severity::Error, None, format!("can't be rendered: {e}")),
+        }
+    } else if report.multiple {
+        report.issue(Severity::Warning, None, hd());
+    }
+    report.valid = !report
+        .issues
+        .iter()
+        .any(|issue| issue.severity == Severity::Error);
+    report
+}
+
+/// Check uploaded or downloaded skin can be rendered by its PNG header, before decoding it.
+///
+/// Only [UV_SIZE] skins are rendered, so bigger images are never decoded.
+pub fn check_header(data: &[u8]) -> Result<(), Error> {
+    let (w, h) = PngDecoder::new(Cursor::new(data))
+        .map_err(|e| Error::InvalidSkin(format!("can't decode PNG header: {e}")))?
+        .dimensions();
+    if !is_multiple(w, h) {
+        return Err(Error::InvalidSkin(not_multiple(w, h)));
+    }
+    if (w, h) != UV_SIZE {
+        return Err(Error::InvalidSkin(hd()));
+    }
+    Ok(())
+}
+
+/// Whether `w`x`h` is [UV_SIZE] or its multiple, like DDNet HD skins
+fn is_multiple(
+    w: u32,
+    h: u32,
+) -> bool {
+    w > 0 && w.is_multiple_of(UV_SIZE.0) && h * 2 == w
+}
+
+fn not_multiple(
+    w: u32,
+    h: u32,
+) -> String {
+    format!(
+        "size {w}x{h} is not a multiple of {}x{}",
+        UV_SIZE.0, UV_SIZE.1
+    )
+}
+
+fn hd() -> String {
+    format!(
+        "HD skins work in DDNet, but only {}x{} skins are rendered here",
+        UV_SIZE.0, UV_SIZE.1
+    )
+}
+
+/// Look for empty parts and broken transparency, `scale` is the multiple of 256x128
+fn lint_parts(
+    report: &mut SkinReport,
+    img: &RgbaImage,
+    scale: u32,
+) {
+    let uv = TEE_UV_LAYOUT;
+    let [normal, angry, pain, happy, _, surprise] = uv.eyes;
+    let parts = [
+        ("body", uv.body, Severity::Error),
+        ("body_shadow", uv.body_shadow, Severity::Warning),
+        ("feet", uv.feet, Severity::Error),
+        ("feet_shadow", uv.feet_shadow, Severity::Warning),
+        ("hand", uv.hand, Severity::Warning),
+        ("hand_shadow", uv.hand_shadow, Severity::Warning),
+        ("eye_normal", normal, Severity::Warning),
+        ("eye_angry", angry, Severity::Warning),
+        ("eye_pain", pain, Severity::Warning),
+        ("eye_happy", happy, Severity::Warning),
+        ("eye_surprise", surprise, Severity::Warning),
+    ];
+    for (name, part, empty) in parts {
+        let alpha = alpha(img, part, scale);
+        if alpha.translucent + alpha.opaque == 0 {
+            report.issue(empty, Some(name), "part is empty");
+            continue;
+        }
+        if !matches!(name, "body" | "feet") {
+            continue;
+        }
+        if alpha.transparent == 0 {
+            report.issue(
+                Severity::Error,
+                Some(name),
+                "part has no transparent pixels, its background was probably not removed",
+            );
+        } else if alpha.translucent > alpha.opaque {
+            report.issue(
+                Severity::Warning,
+                Some(name),
+                "part is mostly semi-transparent and looks see-through in game",
+            );
+        }
+    }
+}
+
+/// Count pixels of `part` scaled by `scale` by their alpha
+fn alpha(
+    img: &RgbaImage,
+    part: UVPart,
+    scale: u32,
+) -> Alpha {
+    let mut alpha = Alpha::default();
+    for y in part.y * scale..(part.y + part.h) * scale {
+        for x in part.x * scale..(part.x + part.w) * scale {
+            match img.get_pixel(x, y)[3] {
+                0 => alpha.transparent += 1,
+                255 => alpha.opaque += 1,
+                _ => alpha.translucent += 1,
+            }
+        }
+    }
+    alpha
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use image::{ImageFormat, Rgba, RgbaImage};
+
+    use crate::{
+        error::Error,
+        render::validate::{Severity, check_header, validate},
+    };
+
+    fn png(img: &RgbaImage) -> Vec<u8> {
+        let mut buf = Vec::new();
+        img.write_to(&mut Cursor::new(&mut buf), ImageFormat::Png)
+            .unwrap();
+        buf
+    }
+
+    #[test]
+    fn skin_validation() {
+        let opaque = validate(&png(&RgbaImage::from_pixel(256, 128, Rgba([1, 2, 3, 255]))));
+        assert!(!opaque.valid && opaque.multiple && opaque.renderable);
+        assert_eq!(
+            opaque
+                .issues
+                .iter()
+                .filter_map(|issue| issue.part.as_deref())
+                .collect::<Vec<_>>(),
+            ["body", "feet"]
+        );
+
+        let hd = validate(&png(&RgbaImage::new(512, 256)));
+        assert!(!hd.valid && hd.multiple && !hd.renderable);
+        assert!(
+            hd.issues
+                .iter()
+                .any(|issue| issue.severity == Severity::Warning)
+        );
+
+        let small = validate(&png(&RgbaImage::new(100, 50)));
+        assert!(!small.valid && !small.multiple && !small.renderable);
+
+        let garbage = validate(b"not an image");
+        assert!(!garbage.valid && garbage.width.is_none());
+    }
+
+    #[test]
+    fn header_check() {
+        check_header(&png(&RgbaImage::new(256, 128))).unwrap();
+        for data in [
+            png(&RgbaImage::new(512, 256)),
+            png(&RgbaImage::new(100, 50)),
+            b"not an image".to_vec(),
+        ] {
+            assert!(matches!(check_header(&data), Err(Error::InvalidSkin(_))));
+        }
+    }
+}
@@ -16,13 +16,18 @@ use crate::{
     },
 };
 
+/// Directory listing of skins made by DDNet
+pub const OFFICIAL_SKINS_URL: &str = "https://ddnet.org/skins/skin/";
+/// Directory listing of skins made by players
+pub const COMMUNITY_SKINS_URL: &str = "https://ddnet.org/skins/skin/community/";
+
+/// Directories of Teeworlds 0.7 skin parts
+pub const SEVEN_PARTS: [&str; 6] = ["body", "marking", "decoration", "hands", "feet", "eyes"];
+
 pub async fn try_sync_skins(lock: Arc<LockStore>) -> Result<(), Error> {
     let client = Client::new();
 
-    let urls = vec![
-        "https://ddnet.org/skins/skin/",
-        "https://ddnet.org/skins/skin/community/",
-    ];
+    let urls = vec![OFFICIAL_SKINS_URL, COMMUNITY_SKINS_URL];
 
     let fetches = urls.into_iter().map(|url| {
         let client = client.clone();
@@ -34,7 +39,45 @@ pub async fn try_sync_skins(lock: Arc<LockStore>) -> Result<(), Error> {
     });
 
     let results: Vec<Vec<SkinMeta>> = future::try_join_all(fetches).await?;
+    update(lock, &client, results).await
+}
+
+/// Sync Teeworlds 0.7 skin parts from `source`, which has a directory per [SEVEN_PARTS] item.
+///
+/// Parts are stored as `<part>/<name>` in the `lock`.
+pub async fn try_sync_seven(
+    lock: Arc<LockStore>,
+    source: &str,
+) -> Result<(), Error> {
+    let client = Client::new();
+
+    let fetches = SEVEN_PARTS.into_iter().map(|part| {
+        let client = client.clone();
+        let url = format!("{source}{part}/");
+        async move {
+            let list = fetch_skin_list(&client, &url).await?;
+            info!("{url} -> len PNGs: {:?}", list.len());
+            Ok::<_, Error>(
+                list.into_iter()
+                    .map(|skin| SkinMeta {
+                        name: format!("{part}/{}", skin.name),
+                        ..skin
+                    })
+                    .collect(),
+            )
+        }
+    });
+
+    let results: Vec<Vec<SkinMeta>> = future::try_join_all(fetches).await?;
+    update(lock, &client, results).await
+}
 
+/// Download new or outdated skins from fetched lists
+async fn update(
+    lock: Arc<LockStore>,
+    client: &Client,
+    results: Vec<Vec<SkinMeta>>,
+) -> Result<(), Error> {
     let map = tokio::task::spawn_blocking(move || {
         use rayon::prelude::*;
         results
@@ -65,7 +108,7 @@ pub async fn try_sync_skins(lock: Arc<LockStore>) -> Result<(), Error> {
         info!("Nothing to update");
     } else {
         info!("Found something to update: {:#?}", to_update);
-        lock.apply_updates(&to_update, &client).await?;
+        lock.apply_updates(&to_update, client).await?;
     }
 
     Ok(())
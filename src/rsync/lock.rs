@@ -1,14 +1,14 @@
 use std::{
     path::{Path, PathBuf},
-    sync::Arc,
+    sync::{Arc, RwLock},
+    time::{Duration, SystemTime},
 };
 
 use dashmap::DashMap;
-use image::ImageFormat;
+use ohkami::openapi::{self, Schema, SchemaRef};
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use tee_morphosis::tee::{Tee, hsl::ddnet_color_to_hsl, parts::TeePart, skin::TEE_SKIN_LAYOUT};
 use tokio::{
     fs,
     io::{self, AsyncWriteExt},
@@ -17,7 +17,18 @@ use tokio::{
 };
 use tracing::{error, info, warn};
 
-use crate::{app::skin::SkinQuery, cache::Cache, error::Error, rsync::parser::SkinMeta};
+use crate::{
+    app::skin::{Kind, SkinQuery, State},
+    cache::Cache,
+    error::Error,
+    render::{self, seven::Parts},
+    rsync::{COMMUNITY_SKINS_URL, parser::SkinMeta},
+};
+
+/// Skin DDNet draws for frozen players, overridden by `FROZEN_SKIN`
+pub const DEFAULT_FROZEN_SKIN: &str = "x_ninja";
+/// Skin rendered instead of unknown names if `FALLBACK_SKIN` is not set
+pub const DEFAULT_FALLBACK_SKIN: &str = "default";
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct LockMeta {
@@ -29,11 +40,83 @@ pub struct LockMeta {
     pub ita: String,
 }
 
+/// Upper bound for "did you mean" suggestions of an unknown skin name
+pub const MAX_SUGGESTIONS: usize = 5;
+
+/// Month abbreviations of directory listings
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+/// Skin list a stored skin was synced from
+pub enum Source {
+    /// [crate::rsync::OFFICIAL_SKINS_URL]
+    Official,
+    /// [COMMUNITY_SKINS_URL]
+    Community,
+}
+
+impl Schema for Source {
+    fn schema() -> impl Into<SchemaRef> {
+        openapi::string().enumerates(["official", "community"])
+    }
+}
+
+impl LockMeta {
+    /// Skin list of [LockMeta::origin]
+    pub fn source(&self) -> Source {
+        match self.origin.starts_with(COMMUNITY_SKINS_URL) {
+            true => Source::Community,
+            false => Source::Official,
+        }
+    }
+
+    /// Parse `ita` of a directory listing, like `09-Nov-2024 12:00` in UTC
+    pub fn modified(&self) -> Option<SystemTime> {
+        let (date, time) = self.ita.split_once(' ')?;
+        let mut date = date.split('-');
+        let day = date.next()?.parse::<i64>().ok()?;
+        let month = date.next()?;
+        let month = MONTHS.iter().position(|x| *x == month)? as i64 + 1;
+        let year = date.next()?.parse::<i64>().ok()?;
+        let (hour, minute) = time.trim().split_once(':')?;
+        let (hour, minute) = (hour.parse::<u64>().ok()?, minute.parse::<u64>().ok()?);
+
+        // Days since 1970-01-01 of the proleptic Gregorian calendar
+        let (y, m) = if month <= 2 { (year - 1, month + 9) } else { (year, month - 3) };
+        let era = y.div_euclid(400);
+        let yoe = y - era * 400;
+        let doy = (153 * m + 2) / 5 + day - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        let days = u64::try_from(era * 146_097 + doe - 719_468).ok()?;
+
+        Some(
+            SystemTime::UNIX_EPOCH + Duration::from_secs(days * 86_400 + hour * 3600 + minute * 60),
+        )
+    }
+
+    /// [LockMeta::modified] as unix time in seconds
+    pub fn updated(&self) -> Option<u64> {
+        self.modified()?
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .ok()
+            .map(|updated| updated.as_secs())
+    }
+}
+
 #[derive(Debug)]
 pub struct LockStore {
     /// Path to store
     pub path: PathBuf,
     pub store: Arc<DashMap<String, LockMeta>>,
+    /// Skins added by the last sync which added any, kept in `recent.json`
+    pub recent: RwLock<Vec<String>>,
+    /// Skin drawn for frozen players
+    pub frozen_skin: String,
+    /// Skin rendered instead of unknown names, set by `FALLBACK_SKIN`
+    pub fallback_skin: Option<String>,
 }
 
 pub type Lock = Arc<LockStore>;
@@ -46,11 +129,22 @@ impl LockStore {
         Ok(())
     }
 
+    /// Save [LockStore::recent]
+    pub async fn save_recent(&self) -> io::Result<()> {
+        let file = serde_json::to_string_pretty(&*self.recent.read().unwrap()).unwrap();
+        fs::write(&self.path.join("recent.json"), file).await?;
+        Ok(())
+    }
+
     pub async fn read(path: impl AsRef<Path>) -> io::Result<Self> {
         let path = path.as_ref();
         let lock_path = path.join("lock.json");
 
         fs::create_dir(path).await.ok();
+        let recent = match fs::read_to_string(path.join("recent.json")).await {
+            Ok(content) => RwLock::new(serde_json::from_str(&content)?),
+            Err(_) => Default::default(),
+        };
 
         // Если файл непустой — читаем содержимое
         if lock_path.exists() {
@@ -60,6 +154,9 @@ impl LockStore {
             Ok(Self {
                 path: path.to_path_buf().canonicalize().unwrap(),
                 store: Arc::new(inner),
+                recent,
+                frozen_skin: DEFAULT_FROZEN_SKIN.to_string(),
+                fallback_skin: None,
             })
         } else {
             let inner = Arc::new(DashMap::new());
@@ -67,6 +164,9 @@ impl LockStore {
             Ok(Self {
                 path: path.to_path_buf().canonicalize().unwrap(),
                 store: inner,
+                recent,
+                frozen_skin: DEFAULT_FROZEN_SKIN.to_string(),
+                fallback_skin: None,
             })
         }
     }
@@ -98,6 +198,8 @@ impl LockStore {
         let semaphore = Arc::new(Semaphore::new(10));
         let mut join_set = JoinSet::new();
         let mut errors = vec![];
+        // The first sync into an empty store adds everything, which is nothing to review
+        let fresh = self.store.is_empty();
 
         for skin in updated.iter().cloned() {
             let permit = semaphore.clone().acquire_owned().await.unwrap();
@@ -106,6 +208,10 @@ impl LockStore {
             let save_path = self.path.clone().join(&skin.name).with_extension("png");
 
             join_set.spawn(async move {
+                // 0.7 parts are stored in a directory per part
+                if let Some(dir) = save_path.parent() {
+                    fs::create_dir_all(dir).await?;
+                }
                 let result = match client.get(&skin.origin).send().await {
                     Ok(resp) => match resp.bytes().await {
                         Ok(bytes) => {
@@ -131,14 +237,14 @@ impl LockStore {
                                 },
                             );
 
-                            match existed {
+                            match &existed {
                                 Some(e) => {
                                     info!(name=%skin.name, path=?e.path, "🔄 Replaced skin")
                                 }
                                 None => info!(name=%skin.name, path_to_save=?save_path, "🆕 Added new skin"),
                             }
 
-                            Ok(())
+                            Ok((skin.name, existed.is_none()))
                         }
                         Err(e) => Err(Error::DownloadFailed {
                             name: skin.name.clone(),
@@ -156,16 +262,27 @@ impl LockStore {
         }
 
         // Собираем результаты всех задач
+        let mut recent = vec![];
         while let Some(res) = join_set.join_next().await {
             if let Err(e) = res {
                 error!("⚠️ JoinError: {e:?}");
             } else if let Ok(Err(err)) = res {
                 error!("⚠️ {:?}", err);
                 errors.push(err);
+            } else if let Ok(Ok((name, added))) = res
+                && added
+            {
+                recent.push(name);
             }
         }
 
         self.save().await?;
+        // Replaced skins aren't new, and a sync without new skins keeps the previous ones
+        if !fresh && !recent.is_empty() {
+            recent.sort();
+            *self.recent.write().unwrap() = recent;
+            self.save_recent().await?;
+        }
 
         if !errors.is_empty() {
             for e in &errors {
@@ -176,44 +293,379 @@ impl LockStore {
         Ok(())
     }
 
+    /// Name of the stored skin [SkinQuery] is rendered from
+    pub fn source<'a>(
+        &'a self,
+        query: &'a SkinQuery,
+    ) -> &'a str {
+        match query.player.state {
+            State::Normal => &query.name,
+            State::Frozen => &self.frozen_skin,
+        }
+    }
+
+    /// [LockMeta] of the stored skin [SkinQuery] is rendered from.
+    ///
+    /// `name` must be stored even if it's not rendered, the frozen skin missing is
+    /// a server error.
+    pub fn meta(
+        &self,
+        query: &SkinQuery,
+    ) -> Result<LockMeta, Error> {
+        if !self.store.contains_key(&query.name) {
+            return Err(self.not_found(&query.name));
+        }
+        self.store
+            .get(self.source(query))
+            .map(|x| x.value().clone())
+            .ok_or_else(|| Error::FrozenSkinNotFound(self.frozen_skin.clone()))
+    }
+
+    /// [meta](Self::meta) of [SkinQuery], switching an unknown `name` to the fallback skin.
+    ///
+    /// The fallback of the query, or [fallback_skin](Self::fallback_skin) if it has none, is
+    /// used unless it's `none`. It's cleared either way, so it's not a part of the cache key.
+    /// Returns the name of the fallback if it's rendered instead.
+    pub fn resolve(
+        &self,
+        query: &mut SkinQuery,
+    ) -> Result<(LockMeta, Option<String>), Error> {
+        let fallback = query.player.fallback.take();
+        match self.meta(query) {
+            Err(e @ Error::SkinNotFound { .. }) => {
+                let Some(name) = fallback
+                    .or_else(|| self.fallback_skin.clone())
+                    .filter(|name| name != "none" && self.store.contains_key(name))
+                else {
+                    return Err(e);
+                };
+                query.name = name.clone();
+                Ok((self.meta(query)?, Some(name)))
+            }
+            meta => Ok((meta?, None)),
+        }
+    }
+
+    /// [Error::SkinNotFound] with stored names close to `name`
+    pub fn not_found(
+        &self,
+        name: &str,
+    ) -> Error {
+        Error::SkinNotFound {
+            name: name.to_string(),
+            suggestions: self.suggestions(name),
+        }
+    }
+
+    /// Stored names closest to `name`, ignoring case and `_` vs whitespace.
+    ///
+    /// Names containing `name` or within an edit distance of a third of its length qualify,
+    /// closest first.
+    pub fn suggestions(
+        &self,
+        name: &str,
+    ) -> Vec<String> {
+        let name = normalize(name);
+        if name.is_empty() {
+            return Vec::new();
+        }
+        let max = (name.chars().count() / 3).max(1);
+        let mut close = self
+            .store
+            .iter()
+            .filter_map(|x| {
+                let stored = normalize(x.key());
+                let distance = match stored.contains(&name) {
+                    true => (stored.len() - name.len()).min(max),
+                    false => distance(&stored, &name),
+                };
+                (distance <= max).then(|| (distance, x.key().clone()))
+            })
+            .collect::<Vec<_>>();
+        close.sort();
+        close
+            .into_iter()
+            .take(MAX_SUGGESTIONS)
+            .map(|(_, name)| name)
+            .collect()
+    }
+
+    /// Read stored UV atlas of the skin
+    pub async fn uv(
+        &self,
+        name: &str,
+    ) -> Result<Vec<u8>, Error> {
+        let path = self
+            .store
+            .get(name)
+            .ok_or_else(|| self.not_found(name))?
+            .value()
+            .path
+            .clone();
+        fs::read(path).await.map_err(Error::Io)
+    }
+
+    /// Check Teeworlds 0.7 parts of [SkinQuery] are stored, without reading them
+    pub fn check_seven(
+        &self,
+        query: &SkinQuery,
+    ) -> Result<(), Error> {
+        let Kind::Seven(seven) = &query.kind else {
+            return Err(Error::InvalidQuery("expected 0.7 skin".to_string()));
+        };
+        let parts = [
+            ("body", Some(&query.name)),
+            ("marking", seven.marking.as_ref()),
+            ("decoration", seven.decoration.as_ref()),
+            ("hands", Some(&seven.hands)),
+            ("feet", Some(&seven.feet)),
+            ("eyes", Some(&seven.eyes)),
+        ];
+        for (part, name) in parts {
+            if let Some(name) = name {
+                let key = format!("{part}/{name}");
+                if !self.store.contains_key(&key) {
+                    return Err(self.not_found(&key));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Return tee composed from Teeworlds 0.7 parts of [SkinQuery] and cache result to the [Cache]
+    pub async fn get_seven(
+        &self,
+        cache: Cache,
+        query: SkinQuery,
+    ) -> Result<Vec<u8>, Error> {
+        let Kind::Seven(seven) = &query.kind else {
+            return Err(Error::InvalidQuery("expected 0.7 skin".to_string()));
+        };
+        let optional = async |part: &str, name: &Option<String>| match name {
+            Some(name) => self.uv(&format!("{part}/{name}")).await.map(Some),
+            None => Ok(None),
+        };
+        let parts = Parts {
+            body: self.uv(&format!("body/{}", query.name)).await?,
+            marking: optional("marking", &seven.marking).await?,
+            decoration: optional("decoration", &seven.decoration).await?,
+            hands: self.uv(&format!("hands/{}", seven.hands)).await?,
+            feet: self.uv(&format!("feet/{}", seven.feet)).await?,
+            eyes: self.uv(&format!("eyes/{}", seven.eyes)).await?,
+        };
+        let seven = seven.clone();
+        let key = query.clone();
+        let tee = spawn_blocking(
+            #[inline]
+            move || render::seven::render(&parts, &seven, &query),
+        )
+        .await??;
+        cache.save(key, tee.clone()).await?;
+        Ok(tee.to_vec())
+    }
+
     /// Return [Tee] by [SkinQuery] and cache reuslt to the [Cache]
     pub async fn get(
         &self,
         cache: Cache,
         query: SkinQuery,
     ) -> Result<Vec<u8>, Error> {
-        let uv = fs::read(
-            &self
-                .store
-                .get(&query.name)
-                .ok_or(Error::QueryNameNotFound)?
-                .value()
-                .path,
-        )
-        .await
-        .map_err(Error::Io)?;
+        // Cache key keeps the name of the player's own skin
+        let uv = fs::read(self.meta(&query)?.path).await?;
+        let key = query.clone();
         let tee = spawn_blocking(
             #[inline]
-            move || {
-                Tee::new(uv.into(), ImageFormat::Png).map(|mut tee| {
-                    if let Some(value) = query.body {
-                        tee.apply_hsl_to_parts(
-                            ddnet_color_to_hsl(value),
-                            &[TeePart::Body, TeePart::BodyShadow],
-                        );
-                    }
-                    if let Some(value) = query.feet {
-                        tee.apply_hsl_to_parts(
-                            ddnet_color_to_hsl(value),
-                            &[TeePart::Feet, TeePart::FeetShadow],
-                        );
-                    }
-                    tee.compose_png(TEE_SKIN_LAYOUT, tee_morphosis::tee::parts::EyeType::Happy)
-                })
-            },
+            move || render::skin(uv, &query),
         )
-        .await???;
-        cache.save(query.clone(), tee.clone()).await?;
+        .await??;
+        cache.save(key, tee.clone()).await?;
         Ok(tee.to_vec())
     }
 }
+
+/// Lowercase `name` with whitespaces and `-` as `_`, how players tend to misspell skins
+fn normalize(name: &str) -> String {
+    name.trim()
+        .chars()
+        .map(|ch| match ch {
+            ch if ch.is_whitespace() || ch == '-' => '_',
+            ch => ch.to_ascii_lowercase(),
+        })
+        .collect()
+}
+
+/// Levenshtein distance of `a` and `b` by chars
+fn distance(
+    a: &str,
+    b: &str,
+) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+    for (i, a) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, b) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(a != *b);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::skin::PlayerOptions;
+
+    #[test]
+    fn name_suggestions() {
+        assert_eq!(distance("kitten", "sitting"), 3);
+        assert_eq!(distance("", "abc"), 3);
+        assert_eq!(normalize(" Pinky Pie-2 "), "pinky_pie_2");
+
+        let lock = LockStore {
+            path: PathBuf::new(),
+            store: Arc::default(),
+            recent: RwLock::default(),
+            frozen_skin: DEFAULT_FROZEN_SKIN.to_string(),
+            fallback_skin: None,
+        };
+        for name in [
+            "default",
+            "Default_2",
+            "x_ninja",
+            "bluekitty",
+            "redkitty",
+            "kitty",
+        ] {
+            let meta = LockMeta {
+                path: format!("{name}.png"),
+                origin: String::new(),
+                ita: String::new(),
+            };
+            lock.store.insert(name.to_string(), meta);
+        }
+        assert_eq!(lock.suggestions("defualt"), ["default"]);
+        assert_eq!(lock.suggestions("DEFAULT"), ["default", "Default_2"]);
+        assert_eq!(lock.suggestions("X Ninja"), ["x_ninja"]);
+        assert_eq!(lock.suggestions("kity"), ["kitty"]);
+        assert_eq!(
+            lock.suggestions("Kitty"),
+            ["kitty", "bluekitty", "redkitty"]
+        );
+        assert!(lock.suggestions("zzzzzz").is_empty());
+    }
+
+    #[test]
+    fn fallback_resolution() {
+        let lock = LockStore {
+            path: PathBuf::new(),
+            store: Arc::default(),
+            recent: RwLock::default(),
+            frozen_skin: DEFAULT_FROZEN_SKIN.to_string(),
+            fallback_skin: Some(DEFAULT_FALLBACK_SKIN.to_string()),
+        };
+        for name in ["default", "x_ninja"] {
+            let meta = LockMeta {
+                path: format!("{name}.png"),
+                origin: String::new(),
+                ita: String::new(),
+            };
+            lock.store.insert(name.to_string(), meta);
+        }
+        let resolve = |name: &str, fallback: Option<&str>| {
+            let mut query = SkinQuery {
+                name: name.to_string(),
+                player: PlayerOptions {
+                    fallback: fallback.map(str::to_string),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let resolved = lock.resolve(&mut query).map(|(_, fallback)| fallback);
+            assert_eq!(query.player.fallback, None);
+            resolved.map(|fallback| (query.name, fallback))
+        };
+
+        assert_eq!(
+            resolve("x_ninja", Some("default")).unwrap(),
+            ("x_ninja".to_string(), None)
+        );
+        assert_eq!(
+            resolve("nope", None).unwrap(),
+            ("default".to_string(), Some("default".to_string()))
+        );
+        assert_eq!(
+            resolve("nope", Some("x_ninja")).unwrap(),
+            ("x_ninja".to_string(), Some("x_ninja".to_string()))
+        );
+        for fallback in ["none", "missing"] {
+            assert!(matches!(
+                resolve("nope", Some(fallback)),
+                Err(Error::SkinNotFound { .. })
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn recent_skins() {
+        use tokio::{io::AsyncReadExt, net::TcpListener};
+
+        // Answers any request with the same body, like a skin list host serving PNGs
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let host = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut buf = [0; 1024];
+                    let _ = stream.read(&mut buf).await;
+                    let _ = stream
+                        .write_all(
+                            b"HTTP/1.1 200 OK\r\nContent-Length: 3\r\nConnection: close\r\n\r\nPNG",
+                        )
+                        .await;
+                });
+            }
+        });
+
+        let path = std::env::temp_dir().join("cdn-recent-skins");
+        let _ = fs::remove_dir_all(&path).await;
+        fs::create_dir_all(&path).await.unwrap();
+        let lock = LockStore {
+            path,
+            store: Arc::default(),
+            recent: RwLock::default(),
+            frozen_skin: DEFAULT_FROZEN_SKIN.to_string(),
+            fallback_skin: None,
+        };
+        let client = Client::new();
+        let skins = |names: &[&str]| {
+            names
+                .iter()
+                .map(|name| SkinMeta {
+                    origin: format!("http://{host}/{name}.png"),
+                    name: name.to_string(),
+                    ita: String::new(),
+                })
+                .collect::<Vec<_>>()
+        };
+        let recent = |lock: &LockStore| lock.recent.read().unwrap().clone();
+
+        lock.apply_updates(&skins(&["default", "x_ninja"]), &client)
+            .await
+            .unwrap();
+        assert_eq!(lock.store.len(), 2);
+        assert!(recent(&lock).is_empty());
+
+        lock.apply_updates(&skins(&["kitty", "default"]), &client)
+            .await
+            .unwrap();
+        assert_eq!(recent(&lock), ["kitty"]);
+
+        lock.apply_updates(&skins(&["x_ninja"]), &client)
+            .await
+            .unwrap();
+        assert_eq!(recent(&lock), ["kitty"]);
+    }
+}
@@ -78,7 +78,7 @@ mod tests {
             "https://ddnet.org/skins/skin/community/",
         ];
         for url in urls {
-            let mut local_links = fetch_skin_list(&client, &url).await.unwrap();
+            let mut local_links = fetch_skin_list(&client, url).await.unwrap();
             println!("{url} -> len PNGs: {:?}", local_links.len());
             links.append(&mut local_links);
         }